@@ -40,7 +40,10 @@ impl Audio {
     /// A handful of other Errors may be returned if dependent alsa-sys library
     /// function calls fail for other unknown reasons.
     ///
-    pub fn new(card_name: &str, channel_name: &str) -> Result<Self, Box<dyn Error>> {
+    pub fn new(
+        card_name: &str,
+        channel_name: &str,
+    ) -> Result<Self, Box<dyn Error>> {
         let mut audio = Audio {
             current_volume: 0,
             max_volume: 0,
@@ -67,11 +70,16 @@ impl Audio {
     fn setup_selem_id(&mut self) -> Result<(), Box<dyn Error>> {
         unsafe {
             if snd_mixer_selem_id_malloc(&mut self.selem_id_ptr) != 0 {
-                return Err("Call to snd_mixer_selem_id_malloc() failed!".into());
+                return Err(
+                    "Call to snd_mixer_selem_id_malloc() failed!".into()
+                );
             }
 
             snd_mixer_selem_id_set_index(self.selem_id_ptr, 0);
-            snd_mixer_selem_id_set_name(self.selem_id_ptr, self.channel_name.as_ptr());
+            snd_mixer_selem_id_set_name(
+                self.selem_id_ptr,
+                self.channel_name.as_ptr(),
+            );
         }
 
         Ok(())
@@ -98,10 +106,17 @@ impl Audio {
                 .into());
             }
 
-            let regopt_ptr: *mut snd_mixer_selem_regopt = MaybeUninit::zeroed().assume_init();
-            let mut mixer_type_ptr: *mut snd_mixer_class_t = MaybeUninit::zeroed().assume_init();
-
-            if snd_mixer_selem_register(self.handle_ptr, regopt_ptr, &mut mixer_type_ptr) != 0 {
+            let regopt_ptr: *mut snd_mixer_selem_regopt =
+                MaybeUninit::zeroed().assume_init();
+            let mut mixer_type_ptr: *mut snd_mixer_class_t =
+                MaybeUninit::zeroed().assume_init();
+
+            if snd_mixer_selem_register(
+                self.handle_ptr,
+                regopt_ptr,
+                &mut mixer_type_ptr,
+            ) != 0
+            {
                 return Err("Call to snd_mixer_selem_register() failed!".into());
             }
         }
@@ -135,7 +150,8 @@ impl Status for Audio {
 
             snd_mixer_load(self.handle_ptr);
 
-            let selem_ptr = snd_mixer_find_selem(self.handle_ptr, self.selem_id_ptr);
+            let selem_ptr =
+                snd_mixer_find_selem(self.handle_ptr, self.selem_id_ptr);
             if selem_ptr == std::ptr::null_mut() {
                 return Err(format!(
                     "Call to snd_mixer_find_selem() failed! \
@@ -150,9 +166,17 @@ impl Status for Audio {
                 &mut self.min_volume,
                 &mut self.max_volume,
             );
-            snd_mixer_selem_get_playback_volume(selem_ptr, 0, &mut self.current_volume);
+            snd_mixer_selem_get_playback_volume(
+                selem_ptr,
+                0,
+                &mut self.current_volume,
+            );
             let mut muted: i32 = 0;
-            snd_mixer_selem_get_playback_switch(selem_ptr, SND_MIXER_SCHN_MONO, &mut muted);
+            snd_mixer_selem_get_playback_switch(
+                selem_ptr,
+                SND_MIXER_SCHN_MONO,
+                &mut muted,
+            );
 
             self.muted = muted == 0;
 
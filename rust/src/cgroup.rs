@@ -0,0 +1,268 @@
+//! The ```cgroup``` module provides a struct containing the resource usage of a
+//! cgroup v2, such as a systemd slice, session or container, including its
+//! cpu utilization and memory usage relative to its limit.
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use crate::{
+    memory,
+    sysfs::read_string,
+    timesource::{SystemTimeSource, TimeSource},
+    Status,
+};
+
+static CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+pub struct Cgroup {
+    pub name: String,
+    /// The percentage of a single cpu used, which may exceed 100% when the
+    /// cgroup's tasks run on several cpus at once.
+    pub cpu_utilization: f32,
+    pub memory_bytes: u64,
+    /// The lowest ```memory.max``` amongst the cgroup and its ancestors, or
+    /// ```None``` if none of them are limited.
+    pub memory_limit_bytes: Option<u64>,
+    /// The percentage of ```memory_limit_bytes``` used, or of the total
+    /// system memory if the cgroup isn't limited.
+    pub memory_percent: f32,
+    dir: PathBuf,
+    root: PathBuf,
+    usage_usecs: u64,
+    sampled_at: Instant,
+    time: Arc<dyn TimeSource>,
+}
+
+impl Cgroup {
+    /// Where ```path``` is relative to ```/sys/fs/cgroup```, such as
+    /// ```user.slice/user-1000.slice```.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the cgroup doesn't exist or
+    /// its ```cpu.stat``` and ```memory.current``` files can't be read, which
+    /// is the case when the memory controller isn't enabled for it.
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        Cgroup::with_root(CGROUP_ROOT, path, Arc::new(SystemTimeSource))
+    }
+
+    /// Same as ```Cgroup::new```, except that ```path``` is relative to the
+    /// given ```root``` instead of ```/sys/fs/cgroup```, and that samples are
+    /// timed using the given ```time``` source rather than the system's
+    /// clocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{fs, process, sync::Arc, time::Duration};
+    /// use chrono::Utc;
+    /// use dwmstatus::{cgroup::Cgroup, timesource::FakeTimeSource, Status};
+    ///
+    /// let root = std::env::temp_dir()
+    ///     .join(format!("dwmstatus-cgroup-{}", process::id()));
+    /// let dir = root.join("user.slice");
+    /// let time = Arc::new(FakeTimeSource::new(Utc::now()));
+    ///
+    /// fs::create_dir_all(&dir)?;
+    /// fs::write(dir.join("cpu.stat"), "usage_usec 1000000\n")?;
+    /// fs::write(dir.join("memory.current"), "536870912\n")?;
+    /// fs::write(dir.join("memory.max"), "1073741824\n")?;
+    /// fs::write(root.join("memory.max"), "max\n")?;
+    ///
+    /// let mut cgroup = Cgroup::with_root(&root, "user.slice", time.clone())?;
+    /// assert_eq!(cgroup.memory_limit_bytes, Some(1073741824));
+    /// assert_eq!(cgroup.memory_percent, 50.0);
+    ///
+    /// // A cpu and a half were used over the past second
+    /// fs::write(dir.join("cpu.stat"), "usage_usec 2500000\n")?;
+    /// time.advance(Duration::from_secs(1));
+    /// cgroup.update()?;
+    /// assert_eq!(cgroup.cpu_utilization, 150.0);
+    /// # fs::remove_dir_all(&root)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` for the same reasons
+    /// as ```Cgroup::new```.
+    pub fn with_root<P: AsRef<Path>, Q: AsRef<Path>>(
+        root: P,
+        path: Q,
+        time: Arc<dyn TimeSource>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let root = root.as_ref().to_path_buf();
+        let dir = root.join(path.as_ref());
+        let name = dir.file_name().map_or_else(
+            || String::from("/"),
+            |name| name.to_string_lossy().into_owned(),
+        );
+
+        let mut cgroup = Cgroup {
+            name,
+            cpu_utilization: 0.0,
+            memory_bytes: 0,
+            memory_limit_bytes: None,
+            memory_percent: 0.0,
+            usage_usecs: read_usage_usecs(&dir)?,
+            sampled_at: time.instant(),
+            dir,
+            root,
+            time,
+        };
+
+        cgroup.update_memory()?;
+
+        Ok(cgroup)
+    }
+
+    /// Uses the cgroup dwmstatus itself runs in, which is usually the
+    /// graphical session's scope or slice.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/proc/self/cgroup``` can't
+    /// be read or doesn't contain a cgroup v2 entry, as well as for the same
+    /// reasons as ```Cgroup::new```.
+    pub fn current() -> Result<Self, Box<dyn Error>> {
+        let contents = fs::read_to_string("/proc/self/cgroup")?;
+        let path = contents
+            .lines()
+            .find_map(|line| line.strip_prefix("0::"))
+            .ok_or("No cgroup v2 entry found in /proc/self/cgroup!")?;
+
+        Cgroup::new(path.trim_start_matches('/'))
+    }
+
+    /// Uses the first cgroup found named ```unit```, such as
+    /// ```docker-0123abcd.scope``` or ```user@1000.service```, searching
+    /// ```/sys/fs/cgroup``` breadth first.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if no such cgroup exists, as well
+    /// as for the same reasons as ```Cgroup::new```.
+    pub fn for_unit(unit: &str) -> Result<Self, Box<dyn Error>> {
+        let mut dirs = vec![PathBuf::from(CGROUP_ROOT)];
+
+        while !dirs.is_empty() {
+            let mut children = Vec::new();
+
+            for dir in dirs {
+                for entry in fs::read_dir(&dir)?.flatten() {
+                    let path = entry.path();
+
+                    if !path.is_dir() {
+                        continue;
+                    }
+                    if entry.file_name() == unit {
+                        return Cgroup::new(path.strip_prefix(CGROUP_ROOT)?);
+                    }
+
+                    children.push(path);
+                }
+            }
+
+            dirs = children;
+        }
+
+        Err(format!("No cgroup found for unit '{}'!", unit).into())
+    }
+
+    fn update_memory(&mut self) -> Result<(), Box<dyn Error>> {
+        self.memory_bytes =
+            read_string(&self.dir.join("memory.current"))?.parse()?;
+        self.memory_limit_bytes = self
+            .dir
+            .ancestors()
+            .take_while(|dir| dir.starts_with(&self.root))
+            // Unlimited values are written as max, which fails to parse
+            .filter_map(|dir| read_string(&dir.join("memory.max")).ok())
+            .filter_map(|max| max.parse().ok())
+            .min();
+
+        let total_bytes = match self.memory_limit_bytes {
+            Some(limit_bytes) => limit_bytes,
+            None => {
+                let meminfo = memory::parse_meminfo(&fs::read_to_string(
+                    "/proc/meminfo",
+                )?)?;
+
+                meminfo.get("MemTotal").copied().unwrap_or(0) * 1024
+            }
+        };
+
+        self.memory_percent = if total_bytes == 0 {
+            0.0
+        } else {
+            (100.0 * self.memory_bytes as f64 / total_bytes as f64) as f32
+        };
+
+        Ok(())
+    }
+}
+
+impl Status for Cgroup {
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the cgroup was removed, such
+    /// as when its container stopped, or its files can't be parsed.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let usage_usecs = read_usage_usecs(&self.dir)?;
+        let sampled_at = self.time.instant();
+        let elapsed_usecs =
+            sampled_at.duration_since(self.sampled_at).as_micros();
+
+        if elapsed_usecs > 0 {
+            let usage_delta = usage_usecs.saturating_sub(self.usage_usecs);
+
+            self.cpu_utilization =
+                (100.0 * usage_delta as f64 / elapsed_usecs as f64) as f32;
+            self.usage_usecs = usage_usecs;
+            self.sampled_at = sampled_at;
+        }
+
+        self.update_memory()
+    }
+}
+
+/// Returns the ```usage_usec``` value found in the given ```cpu.stat```
+/// contents.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::cgroup::parse_usage_usecs;
+///
+/// let contents =
+///     "usage_usec 8234567\nuser_usec 6000000\nsystem_usec 2234567\n";
+///
+/// assert_eq!(parse_usage_usecs(contents)?, 8234567);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if ```contents``` doesn't contain
+/// a valid ```usage_usec``` line.
+pub fn parse_usage_usecs(contents: &str) -> Result<u64, Box<dyn Error>> {
+    let value = contents
+        .lines()
+        .find_map(|line| line.strip_prefix("usage_usec "))
+        .ok_or("No usage_usec found in cpu.stat!")?;
+
+    Ok(value.trim().parse()?)
+}
+
+fn read_usage_usecs(dir: &Path) -> Result<u64, Box<dyn Error>> {
+    let path = dir.join("cpu.stat");
+    let contents = fs::read_to_string(&path)
+        .map_err(|err| format!("Failed to read {:?}: {}", path, err))?;
+
+    parse_usage_usecs(&contents)
+}
@@ -0,0 +1,565 @@
+//! The ```clock``` module provides a struct containing the current local
+//! timestamp, along with the format and locale used to display it.
+//!
+//! Formats use chrono's strftime-like specifiers, such as ```%A %x``` for the
+//! date or ```%I:%M %p``` for the time, where day and month names follow the
+//! given locale. The hour specifiers of a format are switched between their
+//! 12 and 24 hour forms at runtime.
+//!
+//! Additional IANA time zones may be displayed along with the local time,
+//! each with its own label, using the time zone database bundled with
+//! ```chrono-tz```.
+//!
+//! A background thread requests a refresh on every minute boundary, or every
+//! second one when the format displays seconds, as well as whenever the wall
+//! clock is set or the local time zone changes. Clocks therefore don't need
+//! the statuses to be updated periodically.
+
+use std::{
+    env,
+    error::Error,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, Thread},
+    time::Duration,
+};
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Datelike, FixedOffset, Local, Locale,
+};
+use chrono_tz::Tz;
+
+use crate::{
+    request_refresh,
+    timer::{self, BoundaryTimer, Wake, Waker},
+    timesource::{SystemTimeSource, TimeSource},
+    Status,
+};
+
+pub struct Clock {
+    pub timestamp: DateTime<FixedOffset>,
+    /// The time zone of the timestamp, or ```None``` for the local one.
+    pub timezone: Option<Tz>,
+    pub locale: Locale,
+    pub twelve_hour: bool,
+    /// Whether to display the ISO week number along with the timestamp.
+    pub week: bool,
+    /// The time zones displayed after the local time.
+    pub zones: Vec<Zone>,
+    /// Whether to display a single time zone at a time, starting with the
+    /// local one, rather than all of them.
+    pub cycle: bool,
+    /// The index of the time zone displayed while cycling, where ```0``` is
+    /// the local time zone.
+    pub zone_index: usize,
+    format: String,
+    time: Arc<dyn TimeSource>,
+    stopped: Arc<AtomicBool>,
+    waker: Option<Waker>,
+    thread: Thread,
+}
+
+pub struct Zone {
+    /// The label displayed before the zone's time, such as ```NYC```.
+    pub label: String,
+    pub timezone: Tz,
+}
+
+impl Zone {
+    /// Where ```name``` is an IANA time zone name, such as
+    /// ```America/New_York```.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use dwmstatus::clock::Zone;
+    ///
+    /// let zone = Zone::new("BER", "Europe/Berlin")?;
+    /// let timestamp = Utc.with_ymd_and_hms(2024, 7, 1, 13, 14, 0).unwrap();
+    ///
+    /// assert_eq!(
+    ///     timestamp.with_timezone(&zone.timezone).format("%H:%M").to_string(),
+    ///     "15:14",
+    /// );
+    /// assert!(Zone::new("MARS", "Mars/Olympus_Mons").is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if no time zone named ```name```
+    /// exists.
+    pub fn new(label: &str, name: &str) -> Result<Self, Box<dyn Error>> {
+        let timezone = name
+            .parse()
+            .map_err(|_| format!("No time zone named {} found!", name))?;
+
+        Ok(Zone {
+            label: String::from(label),
+            timezone,
+        })
+    }
+}
+
+impl Clock {
+    /// Where ```format``` is the format of the timestamp, displayed using the
+    /// locale of the ```LC_ALL```, ```LC_TIME``` or ```LANG``` environment
+    /// variables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dwmstatus::clock::Clock;
+    ///
+    /// let date_status = Clock::new("%A %x")?;
+    /// let time_status = Clock::new("%I:%M %p")?;
+    ///
+    /// assert!(!date_status.twelve_hour);
+    /// assert!(time_status.twelve_hour);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```format``` contains an
+    /// invalid specifier.
+    pub fn new(format: &str) -> Result<Self, Box<dyn Error>> {
+        Clock::with_locale(format, get_locale(), Arc::new(SystemTimeSource))
+    }
+
+    /// Where ```zones``` are the labels and IANA names of the time zones to
+    /// display after the local time, using the locale of the environment,
+    /// ```cycle``` whether to display them one at a time, switching to the
+    /// next one on middle click, and ```time``` the source the timestamp is
+    /// read from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use dwmstatus::{clock::Clock, timesource::SystemTimeSource, Status};
+    ///
+    /// let mut world_clock_status = Clock::with_zones(
+    ///     "%H:%M",
+    ///     &[("NYC", "America/New_York"), ("BER", "Europe/Berlin")],
+    ///     true,
+    ///     Arc::new(SystemTimeSource),
+    /// )?;
+    /// assert!(world_clock_status.shown_zones()[0].is_none());
+    ///
+    /// world_clock_status.act(2)?;
+    /// assert_eq!(world_clock_status.shown_zones()[0].unwrap().label, "NYC");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// Each zone follows its own daylight saving time rules, such as New York
+    /// springing forward three weeks before Berlin:
+    ///
+    /// ```
+    /// use std::{sync::Arc, time::Duration};
+    /// use chrono::{TimeZone, Utc};
+    /// use dwmstatus::{clock::Clock, timesource::FakeTimeSource, Status};
+    ///
+    /// let start = Utc.with_ymd_and_hms(2024, 3, 10, 6, 59, 0).unwrap();
+    /// let time = Arc::new(FakeTimeSource::new(start));
+    /// let mut clock = Clock::with_zones(
+    ///     "%H:%M",
+    ///     &[("NYC", "America/New_York"), ("BER", "Europe/Berlin")],
+    ///     true,
+    ///     time.clone(),
+    /// )?;
+    /// clock.act(2)?;
+    ///
+    /// clock.update()?;
+    /// assert_eq!(clock.to_string(), "^c#68a7d4^\u{f017} ^d^NYC 01:59");
+    ///
+    /// time.advance(Duration::from_secs(60));
+    /// clock.update()?;
+    /// assert_eq!(clock.to_string(), "^c#68a7d4^\u{f017} ^d^NYC 03:00");
+    ///
+    /// clock.act(2)?;
+    /// assert_eq!(clock.to_string(), "^c#68a7d4^\u{f017} ^d^BER 08:00");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```format``` contains an
+    /// invalid specifier, or if any of the time zones doesn't exist.
+    pub fn with_zones(
+        format: &str,
+        zones: &[(&str, &str)],
+        cycle: bool,
+        time: Arc<dyn TimeSource>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut clock = Clock::with_locale(format, get_locale(), time)?;
+        clock.cycle = cycle;
+
+        for (label, name) in zones {
+            clock.zones.push(Zone::new(label, name)?);
+        }
+
+        Ok(clock)
+    }
+
+    /// Where ```format``` is the format of the timestamp, displayed using the
+    /// given ```locale```, and ```time``` the source the timestamp is read
+    /// from. The refreshing thread still follows the system's wall clock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use dwmstatus::{
+    ///     clock::{parse_locale, Clock},
+    ///     timesource::SystemTimeSource,
+    /// };
+    ///
+    /// let locale = parse_locale("fr_FR.UTF-8").unwrap();
+    /// let date_status =
+    ///     Clock::with_locale("%A %x", locale, Arc::new(SystemTimeSource))?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// Daylight saving time transitions, such as Berlin springing forward
+    /// from 02:00 to 03:00:
+    ///
+    /// ```
+    /// use std::{sync::Arc, time::Duration};
+    /// use chrono::{Locale, TimeZone, Utc};
+    /// use dwmstatus::{clock::Clock, timesource::FakeTimeSource, Status};
+    ///
+    /// let start = Utc.with_ymd_and_hms(2024, 3, 31, 0, 59, 0).unwrap();
+    /// let time = Arc::new(FakeTimeSource::new(start));
+    /// let mut clock =
+    ///     Clock::with_locale("%H:%M %:z", Locale::POSIX, time.clone())?;
+    /// clock.timezone = Some(chrono_tz::Europe::Berlin);
+    ///
+    /// clock.update()?;
+    /// assert_eq!(clock.to_string(), "^c#68a7d4^\u{f017} ^d^01:59 +01:00");
+    ///
+    /// time.advance(Duration::from_secs(60));
+    /// clock.update()?;
+    /// assert_eq!(clock.to_string(), "^c#68a7d4^\u{f017} ^d^03:00 +02:00");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// Or falling back from 03:00 to 02:00, so that the same local time
+    /// happens twice:
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use chrono::{Locale, TimeZone, Utc};
+    /// use dwmstatus::{clock::Clock, timesource::FakeTimeSource, Status};
+    ///
+    /// let start = Utc.with_ymd_and_hms(2024, 10, 27, 0, 30, 0).unwrap();
+    /// let time = Arc::new(FakeTimeSource::new(start));
+    /// let mut clock =
+    ///     Clock::with_locale("%a %H:%M %:z", Locale::POSIX, time.clone())?;
+    /// clock.timezone = Some(chrono_tz::Europe::Berlin);
+    ///
+    /// clock.update()?;
+    /// assert_eq!(clock.to_string(), "^c#68a7d4^\u{f017} ^d^Sun 02:30 +02:00");
+    ///
+    /// time.set(Utc.with_ymd_and_hms(2024, 10, 27, 1, 30, 0).unwrap());
+    /// clock.update()?;
+    /// assert_eq!(clock.to_string(), "^c#68a7d4^\u{f017} ^d^Sun 02:30 +01:00");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```format``` contains an
+    /// invalid specifier, or if the refreshing thread can't be spawned.
+    pub fn with_locale(
+        format: &str,
+        locale: Locale,
+        time: Arc<dyn TimeSource>,
+    ) -> Result<Self, Box<dyn Error>> {
+        if StrftimeItems::new(format).any(|item| item == Item::Error) {
+            return Err(format!("Invalid clock format {}!", format).into());
+        }
+
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = stopped.clone();
+        let period = if has_specifier(format, &['S', 'T', 'r', 'X', 'c', 's']) {
+            Duration::from_secs(1)
+        } else {
+            Duration::from_secs(60)
+        };
+
+        let timer = match BoundaryTimer::new(period) {
+            Ok(timer) => Some(timer),
+            Err(err) => {
+                eprintln!(
+                    "Failed to create the clock's timer, sleeping instead: {}",
+                    err
+                );
+                None
+            }
+        };
+        let waker = timer.as_ref().map(|timer| timer.waker());
+
+        let handle = thread::Builder::new()
+            .name(String::from("clock"))
+            .spawn(move || refresh(timer, period, &thread_stopped))?;
+
+        Ok(Clock {
+            timestamp: time.now().with_timezone(&Local).fixed_offset(),
+            timezone: None,
+            locale,
+            twelve_hour: is_twelve_hour(format),
+            week: false,
+            zones: Vec::new(),
+            cycle: false,
+            zone_index: 0,
+            format: String::from(format),
+            time,
+            stopped,
+            waker,
+            thread: handle.thread().clone(),
+        })
+    }
+
+    /// Returns the format of the timestamp, using the 12 or 24 hour form of
+    /// its hour specifiers depending on ```twelve_hour```.
+    pub fn format(&self) -> String {
+        to_hour_format(&self.format, self.twelve_hour)
+    }
+
+    /// Returns whether the format displays the time of day, rather than only
+    /// the date.
+    pub fn shows_time(&self) -> bool {
+        has_specifier(
+            &self.format,
+            &['H', 'I', 'k', 'l', 'M', 'S', 'R', 'T', 'r', 'X', 'c'],
+        )
+    }
+
+    /// Returns the time zones to display, where ```None``` stands for the
+    /// local time zone.
+    pub fn shown_zones(&self) -> Vec<Option<&Zone>> {
+        let zones = std::iter::once(None).chain(self.zones.iter().map(Some));
+
+        if self.cycle {
+            zones
+                .skip(self.zone_index % (self.zones.len() + 1))
+                .take(1)
+                .collect()
+        } else {
+            zones.collect()
+        }
+    }
+
+    /// The ISO week number of the timestamp.
+    pub fn week_number(&self) -> u32 {
+        self.timestamp.iso_week().week()
+    }
+}
+
+impl Status for Clock {
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let now = self.time.now();
+
+        self.timestamp = match self.timezone {
+            Some(timezone) => now.with_timezone(&timezone).fixed_offset(),
+            None => now.with_timezone(&Local).fixed_offset(),
+        };
+        Ok(())
+    }
+
+    /// The left mouse button switches between 12 and 24 hour time, the right
+    /// mouse button shows or hides the ISO week number, and the middle mouse
+    /// button displays the next time zone while cycling.
+    fn act(&mut self, button: u8) -> Result<(), Box<dyn Error>> {
+        match button {
+            1 => self.twelve_hour = !self.twelve_hour,
+            2 => {
+                self.zone_index = (self.zone_index + 1) % (self.zones.len() + 1)
+            }
+            3 => self.week = !self.week,
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for Clock {
+    /// Wakes the refreshing thread up so that it stops right away.
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+
+        if let Some(waker) = &self.waker {
+            waker.wake();
+        }
+        self.thread.unpark();
+    }
+}
+
+/// Parses a locale name as found in the ```LANG``` environment variable,
+/// ignoring its codeset and modifier.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::Locale;
+/// use dwmstatus::clock::parse_locale;
+///
+/// assert_eq!(parse_locale("de_DE.UTF-8"), Some(Locale::de_DE));
+/// assert_eq!(parse_locale("ca_ES@valencia"), Some(Locale::ca_ES));
+/// assert_eq!(parse_locale("C"), Some(Locale::POSIX));
+/// assert_eq!(parse_locale("klingon"), None);
+/// ```
+pub fn parse_locale(name: &str) -> Option<Locale> {
+    let name = name.split(['.', '@']).next().unwrap_or_default();
+
+    match name {
+        "C" | "POSIX" => Some(Locale::POSIX),
+        name => name.parse().ok(),
+    }
+}
+
+/// Returns the given format using either the 12 or 24 hour form of its hour
+/// specifiers. An ```%p``` specifier is added after the time when switching
+/// to 12 hour time, and removed when switching to 24 hour time.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::clock::to_hour_format;
+///
+/// assert_eq!(to_hour_format("%H:%M", true), "%I:%M %p");
+/// assert_eq!(to_hour_format("%a %R %d", true), "%a %I:%M %p %d");
+/// assert_eq!(to_hour_format("%I:%M %p", false), "%H:%M");
+/// assert_eq!(to_hour_format("%A %x", false), "%A %x");
+/// ```
+pub fn to_hour_format(format: &str, twelve_hour: bool) -> String {
+    let mut output = String::with_capacity(format.len() + 3);
+    // Where to add the meridiem, right after the last time specifier
+    let mut meridiem = None;
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+
+        let mut specifier = String::from('%');
+        while let Some(padding) =
+            chars.next_if(|c| matches!(c, '-' | '_' | '0'))
+        {
+            specifier.push(padding);
+        }
+        let c = match chars.next() {
+            Some(c) => c,
+            None => {
+                output.push_str(&specifier);
+                break;
+            }
+        };
+
+        match (twelve_hour, c) {
+            (true, 'H') => specifier.push('I'),
+            (true, 'k') => specifier.push('l'),
+            (true, 'R') => specifier.push_str("I:%M"),
+            (true, 'T') => specifier.push_str("I:%M:%S"),
+            (false, 'I') => specifier.push('H'),
+            (false, 'l') => specifier.push('k'),
+            (false, 'r') => specifier.push_str("H:%M:%S"),
+            (false, 'p' | 'P') => {
+                if output.ends_with(' ') {
+                    output.pop();
+                }
+                continue;
+            }
+            (_, c) => specifier.push(c),
+        }
+        output.push_str(&specifier);
+
+        match c {
+            'H' | 'k' | 'R' | 'T' | 'I' | 'l' | 'M' | 'S' => {
+                meridiem = Some(output.len())
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(index) = meridiem {
+        if twelve_hour && !has_specifier(format, &['p', 'P', 'r']) {
+            output.insert_str(index, " %p");
+        }
+    }
+
+    output
+}
+
+/// Requests a refresh at every ```period``` boundary until ```stopped```,
+/// sleeping instead of using ```timer``` if it's missing or fails.
+fn refresh(
+    timer: Option<BoundaryTimer>,
+    period: Duration,
+    stopped: &AtomicBool,
+) {
+    if let Some(mut timer) = timer {
+        while !stopped.load(Ordering::Relaxed) {
+            match timer.wait() {
+                Ok(Wake::Woken) => {}
+                Ok(_) => request_refresh(),
+                Err(err) => {
+                    eprintln!(
+                        "The clock's timer failed, sleeping instead: {}",
+                        err
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
+    // Misses changes of the wall clock and time zone until the next boundary
+    while !stopped.load(Ordering::Relaxed) {
+        let now = timer::since_epoch(&SystemTimeSource);
+        thread::park_timeout(timer::next_boundary(now, period) - now);
+        request_refresh();
+    }
+}
+
+/// Returns the locale of the environment, or ```POSIX``` if it's unset or
+/// unknown.
+fn get_locale() -> Locale {
+    ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| parse_locale(&value))
+        .unwrap_or(Locale::POSIX)
+}
+
+fn is_twelve_hour(format: &str) -> bool {
+    has_specifier(format, &['I', 'l', 'r'])
+}
+
+/// Returns whether ```format``` contains any of the given specifiers, ignoring
+/// padding modifiers and escaped ```%``` characters.
+fn has_specifier(format: &str, specifiers: &[char]) -> bool {
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+
+        match chars.find(|c| !matches!(c, '-' | '_' | '0')) {
+            Some(c) if specifiers.contains(&c) => return true,
+            _ => {}
+        }
+    }
+
+    false
+}
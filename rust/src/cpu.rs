@@ -1,68 +1,283 @@
 //! The ```cpu``` module provides a struct containing information related to the
-//! system's cpu, such as total utilization.
+//! system's cpu, such as total and per-core utilization, as well as a breakdown
+//! of the time spent in each category.
 
-use std::{error::Error, fs, ops::Sub};
+use std::{error::Error, fs};
 
 use crate::Status;
 
 pub struct Cpu {
     pub utilization: f32,
-    idle_jiffies: u32,
-    load_jiffies: u32,
+    pub breakdown: Breakdown,
+    pub core_utilization: Vec<f32>,
+    jiffies: Jiffies,
+    core_jiffies: Vec<Option<Jiffies>>,
 }
 
-impl Cpu {
-    pub fn new() -> Self {
-        Cpu {
-            utilization: 0.0,
-            idle_jiffies: 0,
-            load_jiffies: 0,
+/// Percentages of the total cpu time spent in each category since the
+/// last update.
+///
+/// ```user``` and ```nice``` include the time spent running guests, and
+/// ```irq``` includes the time spent servicing both hardware and
+/// software interrupts.
+#[derive(Clone, Copy, Default)]
+pub struct Breakdown {
+    pub user: f32,
+    pub nice: f32,
+    pub system: f32,
+    pub iowait: f32,
+    pub irq: f32,
+    pub steal: f32,
+}
+
+/// The columns of a ```cpu``` line from ```/proc/stat```.
+#[derive(Clone, Copy, Default)]
+pub struct Jiffies {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+    pub guest: u64,
+    pub guest_nice: u64,
+}
+
+impl Jiffies {
+    /// Parses a ```cpu``` line from ```/proc/stat```. Columns missing on older
+    /// kernels are treated as zero.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an ```Error``` if any column isn't a valid number
+    /// or the line doesn't contain at least the user, nice, system and idle
+    /// columns.
+    pub fn parse(line: &str) -> Result<Self, Box<dyn Error>> {
+        let values = line
+            .split_whitespace()
+            .skip(1)
+            .map(|value| value.parse())
+            .collect::<Result<Vec<u64>, _>>()?;
+
+        if values.len() < 4 {
+            return Err(format!(
+                "Unexpected cpu line in /proc/stat: '{}'",
+                line
+            )
+            .into());
         }
+
+        let column = |index: usize| values.get(index).copied().unwrap_or(0);
+
+        Ok(Jiffies {
+            user: column(0),
+            nice: column(1),
+            system: column(2),
+            idle: column(3),
+            iowait: column(4),
+            irq: column(5),
+            softirq: column(6),
+            steal: column(7),
+            guest: column(8),
+            guest_nice: column(9),
+        })
     }
-}
 
-impl Status for Cpu {
-    /// The correctness of this update function depends on previous values of
-    /// the calling ```Cpu``` struct's properties read from ```/proc/stat```.
+    /// The time spent doing nothing, including waiting on I/O.
+    pub fn idle_total(&self) -> u64 {
+        self.idle + self.iowait
+    }
+
+    /// The sum of all columns, excluding ```guest``` and ```guest_nice``` since
+    /// the kernel already accounts for them in ```user``` and ```nice```.
+    pub fn total(&self) -> u64 {
+        self.user
+            + self.nice
+            + self.system
+            + self.idle
+            + self.iowait
+            + self.irq
+            + self.softirq
+            + self.steal
+    }
+
+    /// Returns the jiffies elapsed since ```previous```.
     ///
-    /// As a result, the first call to update for a ```Cpu``` struct will always
-    /// yield incorrect results.
+    /// Counters that went backwards, as ```iowait``` is known to do, are
+    /// treated as having not changed.
+    pub fn since(&self, previous: &Jiffies) -> Jiffies {
+        Jiffies {
+            user: self.user.saturating_sub(previous.user),
+            nice: self.nice.saturating_sub(previous.nice),
+            system: self.system.saturating_sub(previous.system),
+            idle: self.idle.saturating_sub(previous.idle),
+            iowait: self.iowait.saturating_sub(previous.iowait),
+            irq: self.irq.saturating_sub(previous.irq),
+            softirq: self.softirq.saturating_sub(previous.softirq),
+            steal: self.steal.saturating_sub(previous.steal),
+            guest: self.guest.saturating_sub(previous.guest),
+            guest_nice: self.guest_nice.saturating_sub(previous.guest_nice),
+        }
+    }
+}
+
+impl Cpu {
+    /// Primes the struct with an initial read of ```/proc/stat```, so values
+    /// are averages since boot until the first call to ```update```.
     ///
     /// # Errors
     ///
     /// This method returns an ```Error``` if ```/proc/stat``` cannot be opened
-    /// for reading.
-    fn update(&mut self) -> Result<(), Box<dyn Error>> {
-        let contents = fs::read_to_string("/proc/stat")?;
-        let line = contents.lines().next().unwrap();
+    /// for reading or its contents can't be parsed.
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        Cpu::from_stat(&fs::read_to_string("/proc/stat")?)
+    }
 
-        let values: Vec<u32> = line
-            .split_whitespace()
-            .skip(1)
-            .map(|value| value.parse().unwrap())
-            .collect();
+    /// Same as ```Cpu::new```, except that the given ```contents``` are used
+    /// as the priming read instead of ```/proc/stat```.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dwmstatus::cpu::Cpu;
+    ///
+    /// let mut cpu = Cpu::from_stat(
+    ///     "cpu  100 0 50 800 50 0 0 0 0 0\n\
+    ///      cpu0 90 0 30 350 30 0 0 0 0 0\n\
+    ///      cpu1 10 0 20 450 20 0 0 0 0 0\n\
+    ///      intr 1234",
+    /// )?;
+    /// assert_eq!(cpu.utilization, 15.0);
+    ///
+    /// // 20 guest jiffies are already part of the 60 user jiffies
+    /// cpu.update_from_stat(
+    ///     "cpu  160 0 70 900 60 0 10 0 20 0\n\
+    ///      cpu0 150 0 40 360 35 0 5 0 20 0\n\
+    ///      cpu1 10 0 30 540 25 0 5 0 0 0\n\
+    ///      intr 1240",
+    /// )?;
+    /// assert_eq!(cpu.utilization, 45.0);
+    /// assert_eq!(cpu.breakdown.user, 30.0);
+    /// assert_eq!(cpu.breakdown.iowait, 5.0);
+    /// assert_eq!(cpu.breakdown.irq, 5.0);
+    /// assert_eq!(format!("{:.1}", cpu.core_utilization[0]), "83.3");
+    /// assert_eq!(format!("{:.1}", cpu.core_utilization[1]), "13.6");
+    ///
+    /// // Counters that didn't move leave the previous values untouched
+    /// cpu.update_from_stat(
+    ///     "cpu  160 0 70 900 60 0 10 0 20 0\n\
+    ///      cpu0 150 0 40 360 35 0 5 0 20 0\n\
+    ///      cpu1 10 0 30 540 25 0 5 0 0 0",
+    /// )?;
+    /// assert_eq!(cpu.utilization, 45.0);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_stat(contents: &str) -> Result<Self, Box<dyn Error>> {
+        let mut cpu = Cpu {
+            utilization: 0.0,
+            breakdown: Breakdown::default(),
+            core_utilization: Vec::new(),
+            jiffies: Jiffies::default(),
+            core_jiffies: Vec::new(),
+        };
 
-        let idle_jiffies = values[3]; // 4th numeric column contains idle jiffies
-        let load_jiffies = values.iter().sum();
+        cpu.update_from_stat(contents)?;
 
-        let idle_delta = get_delta(self.idle_jiffies, idle_jiffies);
-        let load_delta = get_delta(self.load_jiffies, load_jiffies);
+        Ok(cpu)
+    }
 
-        if load_delta != 0 {
-            self.utilization = 100.0 * (load_delta - idle_delta) as f32 / load_delta as f32;
+    /// Updates the struct's fields using the given ```/proc/stat``` contents.
+    ///
+    /// # Errors
+    ///
+    /// This method returns an ```Error``` if ```contents``` doesn't contain a
+    /// valid aggregate ```cpu``` line or any of the per-core lines are invalid.
+    pub fn update_from_stat(
+        &mut self,
+        contents: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut lines = contents.lines().filter(|line| line.starts_with("cpu"));
+
+        let line = lines.next().ok_or("No cpu line found in /proc/stat!")?;
+        let jiffies = Jiffies::parse(line)?;
 
-            self.idle_jiffies = idle_jiffies;
-            self.load_jiffies = load_jiffies;
+        if let Some((utilization, breakdown)) =
+            get_usage(&jiffies.since(&self.jiffies))
+        {
+            self.utilization = utilization;
+            self.breakdown = breakdown;
+            self.jiffies = jiffies;
+        }
+
+        let mut online = vec![false; self.core_jiffies.len()];
+
+        for line in lines {
+            let core: usize =
+                line.split_whitespace().next().unwrap_or("")[3..].parse()?;
+            let jiffies = Jiffies::parse(line)?;
+
+            if core >= self.core_jiffies.len() {
+                self.core_jiffies.resize(core + 1, None);
+                self.core_utilization.resize(core + 1, 0.0);
+                online.resize(core + 1, false);
+            }
+            online[core] = true;
+
+            // Cores coming back online are compared against zero, same as
+            // the priming read
+            let previous = self.core_jiffies[core].unwrap_or_default();
+
+            if let Some((utilization, _)) = get_usage(&jiffies.since(&previous))
+            {
+                self.core_utilization[core] = utilization;
+                self.core_jiffies[core] = Some(jiffies);
+            }
+        }
+
+        for (core, _) in
+            online.iter().enumerate().filter(|(_, online)| !**online)
+        {
+            self.core_utilization[core] = 0.0;
+            self.core_jiffies[core] = None;
         }
 
         Ok(())
     }
 }
 
-fn get_delta<T: PartialOrd + Sub<Output = T>>(a: T, b: T) -> T {
-    if a < b {
-        b - a
-    } else {
-        a - b
+impl Status for Cpu {
+    /// # Errors
+    ///
+    /// This method returns an ```Error``` if ```/proc/stat``` cannot be opened
+    /// for reading or its contents can't be parsed.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        self.update_from_stat(&fs::read_to_string("/proc/stat")?)
+    }
+}
+
+/// Returns the utilization and breakdown percentages of the given ```delta```,
+/// or ```None``` if no time passed.
+///
+/// Time stolen by the hypervisor counts towards utilization, since the cpu
+/// was unavailable rather than idle.
+fn get_usage(delta: &Jiffies) -> Option<(f32, Breakdown)> {
+    let total = delta.total();
+
+    if total == 0 {
+        return None;
     }
+
+    let percent = |jiffies: u64| (100.0 * jiffies as f64 / total as f64) as f32;
+    let breakdown = Breakdown {
+        user: percent(delta.user),
+        nice: percent(delta.nice),
+        system: percent(delta.system),
+        iowait: percent(delta.iowait),
+        irq: percent(delta.irq + delta.softirq),
+        steal: percent(delta.steal),
+    };
+
+    Some((percent(total - delta.idle_total()), breakdown))
 }
@@ -0,0 +1,241 @@
+//! The ```cpufreq``` module provides a struct containing information related to
+//! the frequency scaling of the system's cpu cores, such as the average and
+//! highest current frequencies as well as the active governor.
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{sysfs::read_string, Status};
+
+pub struct CpuFreq {
+    pub average_khz: u64,
+    pub max_khz: u64,
+    pub min_limit_khz: u64,
+    pub max_limit_khz: u64,
+    pub hardware_max_khz: u64,
+    pub governor: String,
+    pub energy_performance_preference: Option<String>,
+    policy_dirs: Vec<PathBuf>,
+}
+
+impl CpuFreq {
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use dwmstatus::cpufreq::CpuFreq;
+    ///
+    /// let cpufreq_status = CpuFreq::new();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if
+    /// the ```/sys/devices/system/cpu``` directory can't be opened for
+    /// reading or none of its cpus expose a ```cpufreq``` directory, as is
+    /// common in virtual machines.
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        CpuFreq::with_root("/sys/devices/system/cpu")
+    }
+
+    /// Same as ```CpuFreq::new```, except that cpus are discovered
+    /// within ```root``` instead of ```/sys/devices/system/cpu```.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{fs, process};
+    /// use dwmstatus::{cpufreq::CpuFreq, Status};
+    ///
+    /// let root = std::env::temp_dir()
+    ///     .join(format!("dwmstatus-cpufreq-{}", process::id()));
+    /// for (cpu, khz) in [("cpu0", "800000"), ("cpu1", "2400000")] {
+    ///     let dir = root.join(cpu).join("cpufreq");
+    ///     fs::create_dir_all(&dir)?;
+    ///
+    ///     fs::write(dir.join("scaling_cur_freq"), format!("{}\n", khz))?;
+    ///     fs::write(dir.join("scaling_min_freq"), "400000\n")?;
+    ///     fs::write(dir.join("scaling_max_freq"), "3000000\n")?;
+    ///     fs::write(dir.join("cpuinfo_max_freq"), "3500000\n")?;
+    ///     fs::write(dir.join("scaling_governor"), "powersave\n")?;
+    ///     fs::write(
+    ///         dir.join("scaling_available_governors"),
+    ///         "performance powersave\n",
+    ///     )?;
+    /// }
+    /// // Directories other than cpus' are skipped
+    /// fs::create_dir_all(root.join("cpufreq"))?;
+    ///
+    /// let mut cpufreq = CpuFreq::with_root(&root)?;
+    ///
+    /// assert_eq!(cpufreq.average_khz, 1600000);
+    /// assert_eq!(cpufreq.max_khz, 2400000);
+    /// assert_eq!(cpufreq.governor, "powersave");
+    /// assert_eq!(cpufreq.energy_performance_preference, None);
+    ///
+    /// // Every cpu switches to the governor following the current one
+    /// cpufreq.cycle_governor()?;
+    /// assert_eq!(cpufreq.governor, "performance");
+    /// let governor = root.join("cpu1/cpufreq/scaling_governor");
+    /// assert_eq!(fs::read_to_string(&governor)?, "performance");
+    ///
+    /// // Wrapping around to the first one
+    /// cpufreq.cycle_governor()?;
+    /// assert_eq!(cpufreq.governor, "powersave");
+    /// # fs::remove_dir_all(&root)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```root``` can't be opened
+    /// for reading or none of its cpus expose a ```cpufreq``` directory.
+    pub fn with_root<P: AsRef<Path>>(root: P) -> Result<Self, Box<dyn Error>> {
+        let mut policy_dirs = Vec::new();
+
+        for entry in fs::read_dir(root)? {
+            let path = entry?.path();
+            let is_cpu = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix("cpu"))
+                .is_some_and(|id| id.parse::<u32>().is_ok());
+
+            if is_cpu && path.join("cpufreq").is_dir() {
+                policy_dirs.push(path.join("cpufreq"));
+            }
+        }
+
+        if policy_dirs.is_empty() {
+            return Err("No cpufreq directories found!".into());
+        }
+        policy_dirs.sort();
+
+        let mut cpufreq = CpuFreq {
+            average_khz: 0,
+            max_khz: 0,
+            min_limit_khz: 0,
+            max_limit_khz: 0,
+            hardware_max_khz: 0,
+            governor: String::new(),
+            energy_performance_preference: None,
+            policy_dirs,
+        };
+
+        cpufreq.update()?;
+
+        Ok(cpufreq)
+    }
+
+    /// Switches every cpu to the governor following the current one in
+    /// ```scaling_available_governors```.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the available governors can't
+    /// be read or the new governor can't be written, which usually requires
+    /// root privileges or a udev rule granting write access.
+    pub fn cycle_governor(&mut self) -> Result<(), Box<dyn Error>> {
+        self.cycle("scaling_available_governors", "scaling_governor")?;
+        self.update()
+    }
+
+    /// Switches every cpu to the energy performance preference following the
+    /// current one in ```energy_performance_available_preferences```.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the scaling driver doesn't
+    /// support energy performance preferences or the new preference can't be
+    /// written, for instance when the ```performance``` governor is active.
+    pub fn cycle_energy_performance_preference(
+        &mut self,
+    ) -> Result<(), Box<dyn Error>> {
+        self.cycle(
+            "energy_performance_available_preferences",
+            "energy_performance_preference",
+        )?;
+        self.update()
+    }
+
+    fn cycle(
+        &self,
+        available_file: &str,
+        current_file: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let policy_dir = &self.policy_dirs[0];
+        let available = fs::read_to_string(policy_dir.join(available_file))?;
+        let available: Vec<&str> = available.split_whitespace().collect();
+        let current = read_string(&policy_dir.join(current_file))?;
+
+        let next = match available.iter().position(|value| *value == current) {
+            Some(index) => available[(index + 1) % available.len()],
+            None => available
+                .first()
+                .ok_or(format!("No values found in {}!", available_file))?,
+        };
+
+        for policy_dir in &self.policy_dirs {
+            let path = policy_dir.join(current_file);
+
+            fs::write(&path, next).map_err(|err| {
+                format!("Failed to write '{}' to {:?}: {}", next, path, err)
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Status for CpuFreq {
+    /// Cpus whose frequency can't be read, such as offline cpus, are skipped.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the frequency of every cpu
+    /// or the first cpu's governor and limits can't be read.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let frequencies: Vec<u64> = self
+            .policy_dirs
+            .iter()
+            .filter_map(|policy_dir| {
+                read_khz(&policy_dir.join("scaling_cur_freq")).ok()
+            })
+            .collect();
+
+        if frequencies.is_empty() {
+            return Err("Failed to read the frequency of any cpu!".into());
+        }
+
+        self.average_khz =
+            frequencies.iter().sum::<u64>() / frequencies.len() as u64;
+        self.max_khz = *frequencies.iter().max().unwrap_or(&0);
+
+        let policy_dir = &self.policy_dirs[0];
+
+        self.min_limit_khz = read_khz(&policy_dir.join("scaling_min_freq"))?;
+        self.max_limit_khz = read_khz(&policy_dir.join("scaling_max_freq"))?;
+        self.hardware_max_khz = read_khz(&policy_dir.join("cpuinfo_max_freq"))?;
+        self.governor = read_string(&policy_dir.join("scaling_governor"))?;
+        self.energy_performance_preference =
+            read_string(&policy_dir.join("energy_performance_preference")).ok();
+
+        Ok(())
+    }
+
+    /// The left mouse button cycles governors, while the right mouse button
+    /// cycles energy performance preferences.
+    fn act(&mut self, button: u8) -> Result<(), Box<dyn Error>> {
+        match button {
+            1 => self.cycle_governor(),
+            3 => self.cycle_energy_performance_preference(),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn read_khz(path: &Path) -> Result<u64, Box<dyn Error>> {
+    Ok(read_string(path)?.parse()?)
+}
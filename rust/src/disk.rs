@@ -1,86 +1,364 @@
-//! The ```disk``` module provides a struct containing information related to a
-//! system's filepath, such as the number of free and total bytes.
+//! The ```disk``` module provides a struct containing information related to
+//! the system's mounted filesystems, such as the number of used, available and
+//! total bytes and inodes of each.
 //!
-//! The struct's name can be slightly misleading as it technically doesn't
-//! relate to a disk drive, but can be easily used to represent that information
-//! for the hard drive containing the root filesystem if ```"/"``` is given as
-//! the path in ```Disk::new```.
+//! Mounts may either be given explicitly or discovered
+//! from ```/proc/self/mountinfo```, and are looked up again on every update
+//! so that removable drives come and go without restarting.
 
-use std::{error::Error, ffi::CString, fs, mem::MaybeUninit};
+use std::{error::Error, ffi::CString, fs, io, mem::MaybeUninit};
 
 use libc::statvfs;
 
-use crate::Status;
+use crate::{output::Units, Status};
 
 pub struct Disk {
-    pub free_bytes: u64,
+    pub mounts: Vec<Mount>,
+    /// Filesystem types skipped when discovering mounts.
+    pub excluded_types: Vec<String>,
+    /// The units used when displaying human readable sizes.
+    pub units: Units,
+    mount_points: Option<Vec<String>>,
+}
+
+pub struct Mount {
+    pub label: String,
+    pub mount_point: String,
     pub total_bytes: u64,
-    path: CString,
+    pub used_bytes: u64,
+    /// The bytes available to unprivileged users, which excludes
+    /// ```reserved_bytes```.
+    pub available_bytes: u64,
+    /// The free bytes only available to root, usually 5% of ext4 filesystems.
+    pub reserved_bytes: u64,
+    pub total_inodes: u64,
+    pub free_inodes: u64,
 }
 
-impl Disk {
+/// A line of ```/proc/self/mountinfo```.
+#[derive(Clone)]
+pub struct MountInfo {
+    pub device: String,
+    pub mount_point: String,
+    pub fs_type: String,
+    pub source: String,
+}
+
+/// Pseudo, virtual and read-only image filesystems which aren't worth
+/// displaying.
+static DEFAULT_EXCLUDED_TYPES: [&str; 24] = [
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "devtmpfs",
+    "efivarfs",
+    "fuse.gvfsd-fuse",
+    "fuse.portal",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nsfs",
+    "overlay",
+    "proc",
+    "pstore",
+    "ramfs",
+    "securityfs",
+    "squashfs",
+    "sysfs",
+    "tmpfs",
+];
+
+impl Mount {
+    /// Returns the usage of the filesystem mounted at ```mount_point``` found
+    /// in the given ```statvfs``` result, whose ```f_blocks```, ```f_bfree```
+    /// and ```f_bavail``` are expressed in ```f_frsize``` units, as opposed
+    /// to ```f_bsize``` which is the preferred I/O size.
+    ///
     /// # Examples
     ///
     /// ```
-    /// let disk_status = Disk::new("/");
+    /// use dwmstatus::disk::Mount;
+    ///
+    /// let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    /// stat.f_frsize = 4096;
+    /// stat.f_blocks = 1000;
+    /// stat.f_bfree = 300;
+    /// // 50 of the free blocks are reserved for root
+    /// stat.f_bavail = 250;
+    ///
+    /// let mount = Mount::from_statvfs("/home", &stat);
+    ///
+    /// assert_eq!(mount.label, "home");
+    /// assert_eq!(mount.total_bytes, 1000 * 4096);
+    /// assert_eq!(mount.used_bytes, 700 * 4096);
+    /// assert_eq!(mount.available_bytes, 250 * 4096);
+    /// assert_eq!(mount.reserved_bytes, 50 * 4096);
+    /// // Same as df, reserved blocks count as neither used nor available
+    /// assert_eq!(mount.used_percent(), 100.0 * 700.0 / 950.0);
+    /// ```
+    pub fn from_statvfs(mount_point: &str, stat: &statvfs) -> Self {
+        let fragment_size = stat.f_frsize;
+
+        Mount {
+            label: get_label(mount_point),
+            mount_point: String::from(mount_point),
+            total_bytes: stat.f_blocks * fragment_size,
+            used_bytes: stat.f_blocks.saturating_sub(stat.f_bfree)
+                * fragment_size,
+            available_bytes: stat.f_bavail * fragment_size,
+            reserved_bytes: stat.f_bfree.saturating_sub(stat.f_bavail)
+                * fragment_size,
+            total_inodes: stat.f_files,
+            free_inodes: stat.f_ffree,
+        }
+    }
+
+    /// The percentage of the space usable by unprivileged users that's used,
+    /// which matches the ```Use%``` reported by ```df```.
+    pub fn used_percent(&self) -> f32 {
+        percent(self.used_bytes, self.used_bytes + self.available_bytes)
+    }
+
+    pub fn used_inodes_percent(&self) -> f32 {
+        percent(
+            self.total_inodes.saturating_sub(self.free_inodes),
+            self.total_inodes,
+        )
+    }
+}
+
+impl Disk {
+    /// Where ```mount_points``` are the mount points to display, in order.
+    /// Mount points that aren't currently mounted are skipped until they are.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use dwmstatus::disk::Disk;
+    ///
+    /// let disk_status = Disk::new(&["/", "/home", "/run/media/backup"]);
     /// ```
     ///
     /// # Errors
     ///
-    /// This method will return an ```Error``` if the given path doesn't
-    /// exist in the filesystem, its metadata can't be read, or it can't
-    /// be converted to a valid ```CString```.
-    pub fn new(path: &str) -> Result<Self, Box<dyn Error>> {
-        validate(path)?;
-        let path = CString::new(path)?;
+    /// This method will return an ```Error``` if ```/proc/self/mountinfo```
+    /// can't be read.
+    pub fn new(mount_points: &[&str]) -> Result<Self, Box<dyn Error>> {
+        let mut disk = Disk {
+            mounts: Vec::new(),
+            excluded_types: Vec::new(),
+            units: Units::Iec,
+            mount_points: Some(
+                mount_points
+                    .iter()
+                    .map(|path| String::from(*path))
+                    .collect(),
+            ),
+        };
 
-        Ok(Disk {
-            free_bytes: 0,
-            total_bytes: 0,
-            path,
-        })
+        disk.update()?;
+
+        Ok(disk)
+    }
+
+    /// Displays every mounted filesystem whose type isn't excluded, skipping
+    /// bind mounts of an already displayed filesystem.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/proc/self/mountinfo```
+    /// can't be read.
+    pub fn discover() -> Result<Self, Box<dyn Error>> {
+        let mut disk = Disk {
+            mounts: Vec::new(),
+            excluded_types: DEFAULT_EXCLUDED_TYPES
+                .iter()
+                .map(|fs_type| String::from(*fs_type))
+                .collect(),
+            units: Units::Iec,
+            mount_points: None,
+        };
+
+        disk.update()?;
+
+        Ok(disk)
+    }
+
+    fn select_mounts(&self, mount_infos: Vec<MountInfo>) -> Vec<MountInfo> {
+        match &self.mount_points {
+            Some(mount_points) => mount_points
+                .iter()
+                .filter_map(|mount_point| {
+                    // The last entry wins, since it's the one mounted on top
+                    mount_infos
+                        .iter()
+                        .rposition(|info| info.mount_point == *mount_point)
+                })
+                .map(|index| mount_infos[index].clone())
+                .collect(),
+            None => {
+                let mut devices: Vec<String> = Vec::new();
+
+                mount_infos
+                    .into_iter()
+                    .filter(|info| !self.excluded_types.contains(&info.fs_type))
+                    .filter(|info| {
+                        let seen = devices.contains(&info.device);
+                        devices.push(info.device.clone());
+                        !seen
+                    })
+                    .collect()
+            }
+        }
     }
 }
 
 impl Status for Disk {
+    /// Mounts whose filesystem can't be queried are skipped.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/proc/self/mountinfo```
+    /// can't be read, or after updating the remaining mounts if any of the
+    /// filesystems couldn't be queried, such as an unresponsive network share.
     fn update(&mut self) -> Result<(), Box<dyn Error>> {
-        let stat = Statvfs::new(&self.path);
+        let contents = fs::read_to_string("/proc/self/mountinfo")?;
+        let mount_infos = self.select_mounts(parse_mountinfo(&contents));
+        let mut errors = Vec::new();
+
+        self.mounts.clear();
 
-        self.free_bytes = stat.blocks_free() * stat.block_size();
-        self.total_bytes = stat.blocks_available() * stat.block_size();
+        for info in mount_infos {
+            let stat = match Statvfs::new(&info.mount_point) {
+                Ok(stat) => stat,
+                Err(err) => {
+                    errors.push(format!("{}: {}", info.mount_point, err));
+                    continue;
+                }
+            };
+
+            self.mounts
+                .push(Mount::from_statvfs(&info.mount_point, &stat.0));
+        }
+
+        if !errors.is_empty() {
+            return Err(format!(
+                "Failed to query filesystems: {}",
+                errors.join(", ")
+            )
+            .into());
+        }
 
         Ok(())
     }
 }
 
-fn validate(path: &str) -> Result<(), Box<dyn Error>> {
-    fs::metadata(path)?;
+/// Parses the given ```/proc/self/mountinfo``` contents, skipping any
+/// malformed lines.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::disk::parse_mountinfo;
+///
+/// let mount_infos = parse_mountinfo(
+///     "22 1 259:2 / / rw,relatime shared:1 - ext4 /dev/nvme0n1p2 rw\n\
+///      95 22 8:17 / /run/media/My\\040Drive rw,nosuid shared:52 - vfat /dev/sdb1 rw\n",
+/// );
+///
+/// assert_eq!(mount_infos[0].fs_type, "ext4");
+/// assert_eq!(mount_infos[1].mount_point, "/run/media/My Drive");
+/// assert_eq!(mount_infos[1].device, "8:17");
+/// ```
+pub fn parse_mountinfo(contents: &str) -> Vec<MountInfo> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = fields.nth(2)?;
+            let mount_point = fields.nth(1)?;
 
-    Ok(())
-}
+            // Optional fields are terminated by a single hyphen
+            let mut fields = fields.skip_while(|field| *field != "-").skip(1);
+            let fs_type = fields.next()?;
+            let source = fields.next()?;
 
-struct Statvfs(statvfs);
+            Some(MountInfo {
+                device: String::from(device),
+                mount_point: unescape(mount_point),
+                fs_type: String::from(fs_type),
+                source: unescape(source),
+            })
+        })
+        .collect()
+}
 
-impl Statvfs {
-    pub fn new(path: &CString) -> Self {
-        unsafe {
-            let mut stat: Statvfs = MaybeUninit::uninit().assume_init();
+/// Replaces the octal escapes used by the kernel for spaces, tabs, newlines
+/// and backslashes in mountinfo fields.
+fn unescape(field: &str) -> String {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut rest = field;
 
-            statvfs(path.as_ptr(), &mut stat.0);
+    while let Some(index) = rest.find('\\') {
+        unescaped.push_str(&rest[..index]);
 
-            stat
+        match rest
+            .get(index + 1..index + 4)
+            .map(|code| u8::from_str_radix(code, 8))
+        {
+            Some(Ok(byte)) => {
+                unescaped.push(byte as char);
+                rest = &rest[index + 4..];
+            }
+            _ => {
+                unescaped.push('\\');
+                rest = &rest[index + 1..];
+            }
         }
     }
+    unescaped.push_str(rest);
 
-    pub fn block_size(&self) -> u64 {
-        self.0.f_bsize
+    unescaped
+}
+
+/// Returns the last component of ```mount_point```, or ```/``` for the root.
+fn get_label(mount_point: &str) -> String {
+    match mount_point.rsplit('/').next() {
+        Some(name) if !name.is_empty() => String::from(name),
+        _ => String::from("/"),
     }
+}
 
-    pub fn blocks_available(&self) -> u64 {
-        self.0.f_blocks
+fn percent(value: u64, total: u64) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        (100.0 * value as f64 / total as f64) as f32
     }
+}
+
+struct Statvfs(statvfs);
 
-    pub fn blocks_free(&self) -> u64 {
-        self.0.f_bfree
+impl Statvfs {
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` containing the ```errno```
+    /// description if the ```statvfs``` call fails.
+    pub fn new(path: &str) -> Result<Self, Box<dyn Error>> {
+        let path = CString::new(path)?;
+        let mut stat = MaybeUninit::<statvfs>::uninit();
+
+        unsafe {
+            if statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+
+            Ok(Statvfs(stat.assume_init()))
+        }
     }
 }
@@ -0,0 +1,276 @@
+//! The ```diskio``` module provides a struct containing the read and write
+//! throughput of the system's block devices, as well as how busy they are,
+//! sampled from ```/proc/diskstats```.
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use crate::{
+    rate::Rate,
+    timesource::{SystemTimeSource, TimeSource},
+    Status,
+};
+
+/// ```/proc/diskstats``` always counts sectors of 512 bytes, regardless of the
+/// device's actual sector size.
+static SECTOR_BYTES: u64 = 512;
+
+pub struct DiskIo {
+    pub devices: Vec<Device>,
+    /// The weight given to the newest sample, please refer
+    /// to ```Rate::new```.
+    pub smoothing: f64,
+    device_names: Option<Vec<String>>,
+    path: PathBuf,
+    time: Arc<dyn TimeSource>,
+}
+
+pub struct Device {
+    pub name: String,
+    pub read: Rate,
+    pub write: Rate,
+    /// Milliseconds spent doing I/O per second.
+    pub busy: Rate,
+}
+
+/// The counters of a line of ```/proc/diskstats``` used by ```DiskIo```.
+pub struct DiskStats {
+    pub name: String,
+    pub sectors_read: u64,
+    pub sectors_written: u64,
+    pub io_ticks_ms: u64,
+}
+
+impl Device {
+    /// The percentage of time the device was busy doing I/O.
+    pub fn utilization(&self) -> f64 {
+        (self.busy.per_second / 10.0).min(100.0)
+    }
+}
+
+impl DiskIo {
+    /// Where ```device_names``` are the block devices to sample, such as
+    /// ```nvme0n1``` or ```sda```, or ```None``` to sample every physical,
+    /// non-partition device found in ```/sys/block```.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use dwmstatus::diskio::DiskIo;
+    ///
+    /// let diskio_status = DiskIo::new(Some(&["nvme0n1"]));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/proc/diskstats``` can't
+    /// be read.
+    pub fn new(device_names: Option<&[&str]>) -> Result<Self, Box<dyn Error>> {
+        DiskIo::with_path(
+            device_names,
+            "/proc/diskstats",
+            Arc::new(SystemTimeSource),
+        )
+    }
+
+    /// Same as ```DiskIo::new```, except that devices are sampled from the
+    /// file at ```path``` instead of ```/proc/diskstats```, and that samples
+    /// are timed using the given ```time``` source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{fs, process, sync::Arc, time::Duration};
+    /// use chrono::Utc;
+    /// use dwmstatus::{diskio::DiskIo, timesource::FakeTimeSource, Status};
+    ///
+    /// let path = std::env::temp_dir()
+    ///     .join(format!("dwmstatus-diskstats-{}", process::id()));
+    /// let time = Arc::new(FakeTimeSource::new(Utc::now()));
+    /// fs::write(
+    ///     &path,
+    ///     "   8       0 sda 100 0 2048 50 200 0 4096 80 0 100 130 0 0 0 0\n",
+    /// )?;
+    ///
+    /// let mut diskio =
+    ///     DiskIo::with_path(Some(&["sda"]), &path, time.clone())?;
+    /// diskio.smoothing = 1.0;
+    ///
+    /// fs::write(
+    ///     &path,
+    ///     "   8       0 sda 150 0 4096 70 260 0 8192 90 0 350 160 0 0 0 0\n",
+    /// )?;
+    /// time.advance(Duration::from_secs(2));
+    /// diskio.update()?;
+    ///
+    /// assert_eq!(diskio.devices[0].name, "sda");
+    /// assert_eq!(diskio.read_bytes_per_second(), 524288.0);
+    /// # fs::remove_file(&path)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the file at ```path``` can't
+    /// be read.
+    pub fn with_path<P: AsRef<Path>>(
+        device_names: Option<&[&str]>,
+        path: P,
+        time: Arc<dyn TimeSource>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut diskio = DiskIo {
+            devices: Vec::new(),
+            smoothing: 0.5,
+            device_names: device_names.map(|names| {
+                names.iter().map(|name| String::from(*name)).collect()
+            }),
+            path: path.as_ref().to_path_buf(),
+            time,
+        };
+
+        diskio.update()?;
+
+        Ok(diskio)
+    }
+
+    pub fn read_bytes_per_second(&self) -> f64 {
+        self.devices
+            .iter()
+            .map(|device| device.read.per_second)
+            .sum()
+    }
+
+    pub fn write_bytes_per_second(&self) -> f64 {
+        self.devices
+            .iter()
+            .map(|device| device.write.per_second)
+            .sum()
+    }
+
+    /// The utilization of the busiest device.
+    pub fn utilization(&self) -> f64 {
+        self.devices
+            .iter()
+            .map(|device| device.utilization())
+            .fold(0.0, f64::max)
+    }
+
+    /// Updates the devices' rates using the given ```/proc/diskstats```
+    /// contents sampled at the given instant. Devices that disappeared are
+    /// dropped, while new ones are added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{sync::Arc, time::{Duration, Instant}};
+    /// use dwmstatus::{diskio::DiskIo, timesource::SystemTimeSource};
+    ///
+    /// // Devices are only sampled from the given contents below
+    /// let time = Arc::new(SystemTimeSource);
+    /// let mut diskio = DiskIo::with_path(Some(&["sda"]), "/dev/null", time)?;
+    /// let start = Instant::now();
+    /// diskio.smoothing = 1.0;
+    ///
+    /// diskio.update_from_diskstats(
+    ///     "   8       0 sda 100 0 2048 50 200 0 4096 80 0 100 130 0 0 0 0\n\
+    ///        8       1 sda1 100 0 2048 50 200 0 4096 80 0 100 130 0 0 0 0\n",
+    ///     start,
+    /// );
+    /// diskio.update_from_diskstats(
+    ///     "   8       0 sda 150 0 4096 70 260 0 8192 90 0 350 160 0 0 0 0\n",
+    ///     start + Duration::from_secs(2),
+    /// );
+    ///
+    /// assert_eq!(diskio.read_bytes_per_second(), 524288.0);
+    /// assert_eq!(diskio.write_bytes_per_second(), 1048576.0);
+    /// assert_eq!(diskio.utilization(), 12.5);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn update_from_diskstats(&mut self, contents: &str, at: Instant) {
+        let stats: Vec<DiskStats> = parse_diskstats(contents)
+            .into_iter()
+            .filter(|stats| match &self.device_names {
+                Some(device_names) => device_names.contains(&stats.name),
+                None => is_physical(&stats.name),
+            })
+            .collect();
+
+        self.devices.retain(|device| {
+            stats.iter().any(|stats| stats.name == device.name)
+        });
+
+        for stats in stats {
+            let index = match self
+                .devices
+                .iter()
+                .position(|device| device.name == stats.name)
+            {
+                Some(index) => index,
+                None => {
+                    self.devices.push(Device {
+                        name: stats.name.clone(),
+                        read: Rate::new(self.smoothing),
+                        write: Rate::new(self.smoothing),
+                        busy: Rate::new(self.smoothing),
+                    });
+                    self.devices.len() - 1
+                }
+            };
+            let device = &mut self.devices[index];
+
+            device.read.set_smoothing(self.smoothing);
+            device.write.set_smoothing(self.smoothing);
+            device.busy.set_smoothing(self.smoothing);
+
+            device.read.sample(stats.sectors_read * SECTOR_BYTES, at);
+            device
+                .write
+                .sample(stats.sectors_written * SECTOR_BYTES, at);
+            device.busy.sample(stats.io_ticks_ms, at);
+        }
+    }
+}
+
+impl Status for DiskIo {
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/proc/diskstats```, or
+    /// the file given instead, can't be read.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let contents = fs::read_to_string(&self.path)?;
+
+        self.update_from_diskstats(&contents, self.time.instant());
+
+        Ok(())
+    }
+}
+
+/// Parses the given ```/proc/diskstats``` contents, skipping any malformed
+/// lines.
+pub fn parse_diskstats(contents: &str) -> Vec<DiskStats> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            Some(DiskStats {
+                name: String::from(*fields.get(2)?),
+                sectors_read: fields.get(5)?.parse().ok()?,
+                sectors_written: fields.get(9)?.parse().ok()?,
+                io_ticks_ms: fields.get(12)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Physical devices are backed by an actual device, which virtual devices
+/// such as loop, zram or device mapper ones aren't. Partitions aren't listed
+/// in ```/sys/block``` at all.
+fn is_physical(name: &str) -> bool {
+    Path::new("/sys/block").join(name).join("device").exists()
+}
@@ -0,0 +1,454 @@
+//! The ```latency``` module provides a struct containing the round-trip
+//! latency to a host, along with the percentage of recent probes that were
+//! lost.
+//!
+//! Probes are sent from a background thread so that waiting for replies never
+//! delays other statuses. Unprivileged ICMP echo requests are used when the
+//! system allows them through ```net.ipv4.ping_group_range```, and the time
+//! taken to establish a TCP connection otherwise.
+
+use std::{
+    collections::VecDeque,
+    error::Error,
+    io, mem,
+    net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    sync::{
+        atomic::{AtomicBool, AtomicU16, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    output::Thresholds,
+    timesource::{SystemTimeSource, TimeSource},
+    Status,
+};
+
+/// The number of recent probes over which losses are computed.
+static WINDOW: usize = 10;
+
+static ICMP_ECHO_REQUEST: u8 = 8;
+static ICMP_ECHO_REPLY: u8 = 0;
+static ICMPV6_ECHO_REQUEST: u8 = 128;
+static ICMPV6_ECHO_REPLY: u8 = 129;
+
+static SEQUENCE: AtomicU16 = AtomicU16::new(0);
+
+pub struct Latency {
+    pub host: String,
+    /// The round-trip time of the latest successful probe.
+    pub rtt: Option<Duration>,
+    /// The percentage of the recent probes that got no reply.
+    pub loss_percent: f32,
+    /// The number of probes sent so far, up to the window's size.
+    pub probes: usize,
+    /// The loss percentages at which to warn.
+    pub thresholds: Thresholds,
+    samples: Arc<Mutex<VecDeque<Option<Duration>>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+/// The method a probe used to reach its host.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Method {
+    Icmp,
+    Tcp,
+}
+
+impl Latency {
+    /// Where ```host``` is a host name or address to probe
+    /// every ```interval```, and ```port``` the TCP port to connect to
+    /// whenever ICMP isn't allowed.
+    ///
+    /// Only the first address ```host``` resolves to is probed, so that a
+    /// dual-stack host is probed over either IPv4 or IPv6 depending on the
+    /// resolver's preference, without falling back to the other one.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use dwmstatus::latency::Latency;
+    ///
+    /// let latency_status =
+    ///     Latency::new("1.1.1.1", 443, Duration::from_secs(5));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the probing thread can't be
+    /// spawned.
+    pub fn new(
+        host: &str,
+        port: u16,
+        interval: Duration,
+    ) -> Result<Self, Box<dyn Error>> {
+        Latency::with_time_source(
+            host,
+            port,
+            interval,
+            Arc::new(SystemTimeSource),
+        )
+    }
+
+    /// Same as ```Latency::new```, except that round-trip times are measured
+    /// using the given ```time``` source rather than the system's clock.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the probing thread can't be
+    /// spawned.
+    pub fn with_time_source(
+        host: &str,
+        port: u16,
+        interval: Duration,
+        time: Arc<dyn TimeSource>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let latency = Latency::from_samples(host, &[]);
+
+        let thread_samples = latency.samples.clone();
+        let thread_stopped = latency.stopped.clone();
+        let target = format!("{}:{}", host, port);
+        let timeout = interval.min(Duration::from_secs(2));
+
+        thread::Builder::new().name(String::from("latency")).spawn(
+            move || {
+                while !thread_stopped.load(Ordering::Relaxed) {
+                    let rtt = resolve(&target)
+                        .and_then(|address| {
+                            probe_once(address, timeout, time.as_ref())
+                        })
+                        .ok()
+                        .map(|(rtt, _)| rtt);
+
+                    let mut samples = thread_samples.lock().unwrap();
+                    if samples.len() == WINDOW {
+                        samples.pop_front();
+                    }
+                    samples.push_back(rtt);
+                    drop(samples);
+
+                    thread::sleep(interval);
+                }
+            },
+        )?;
+
+        Ok(latency)
+    }
+
+    /// Returns a status about ```host``` that never probes it, whose updates
+    /// only collect the given probe ```samples```, ```None``` being a lost
+    /// probe, of which only the most recent ones within the window are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dwmstatus::{latency::Latency, Status};
+    ///
+    /// let ms = |ms| Some(Duration::from_millis(ms));
+    ///
+    /// let samples = [ms(20), None, ms(30), None];
+    /// let mut latency = Latency::from_samples("example.org", &samples);
+    /// latency.update()?;
+    /// assert_eq!(latency.probes, 4);
+    /// assert_eq!(latency.loss_percent, 50.0);
+    /// assert_eq!(latency.rtt, ms(30));
+    /// assert!(!latency.unreachable());
+    ///
+    /// // Only the last 10 probes count, all of which were lost
+    /// let samples: Vec<_> = [ms(20)].into_iter().chain([None; 10]).collect();
+    /// let mut latency = Latency::from_samples("example.org", &samples);
+    /// latency.update()?;
+    /// assert_eq!(latency.probes, 10);
+    /// assert_eq!(latency.loss_percent, 100.0);
+    /// assert_eq!(latency.rtt, None);
+    /// assert!(latency.unreachable());
+    ///
+    /// // Nothing was sent yet
+    /// let mut latency = Latency::from_samples("example.org", &[]);
+    /// latency.update()?;
+    /// assert_eq!(latency.loss_percent, 0.0);
+    /// assert!(!latency.unreachable());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_samples(host: &str, samples: &[Option<Duration>]) -> Self {
+        let recent = &samples[samples.len().saturating_sub(WINDOW)..];
+        let mut window = VecDeque::with_capacity(WINDOW);
+        window.extend(recent.iter().copied());
+
+        Latency {
+            host: String::from(host),
+            rtt: None,
+            loss_percent: 0.0,
+            probes: 0,
+            thresholds: Thresholds {
+                warning: 10.0,
+                critical: 50.0,
+            },
+            samples: Arc::new(Mutex::new(window)),
+            stopped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Returns whether probes were sent and none of them got a reply.
+    pub fn unreachable(&self) -> bool {
+        self.probes > 0 && self.loss_percent >= 100.0
+    }
+}
+
+impl Status for Latency {
+    /// Never waits for a probe, it only collects those sent in the background.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let samples = self
+            .samples
+            .lock()
+            .map_err(|_| "Latency probing thread panicked!")?;
+        let lost = samples.iter().filter(|rtt| rtt.is_none()).count();
+
+        self.probes = samples.len();
+        self.loss_percent = if samples.is_empty() {
+            0.0
+        } else {
+            100.0 * lost as f32 / samples.len() as f32
+        };
+        self.rtt = samples.iter().rev().find_map(|rtt| *rtt);
+
+        Ok(())
+    }
+}
+
+impl Drop for Latency {
+    /// Stops the probing thread once it wakes up.
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Sends a single probe to ```address```, using an ICMP echo request if
+/// allowed and a TCP connection to the address' port otherwise, and returns
+/// the round-trip time, as measured by ```time```, along with the method
+/// used.
+///
+/// # Examples
+///
+/// ```
+/// use std::{net::TcpListener, time::Duration};
+/// use dwmstatus::{latency::probe_once, timesource::SystemTimeSource};
+///
+/// let listener = TcpListener::bind("127.0.0.1:0")?;
+/// let address = listener.local_addr()?;
+/// let (rtt, _) =
+///     probe_once(address, Duration::from_secs(1), &SystemTimeSource)?;
+///
+/// assert!(rtt < Duration::from_secs(1));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if no reply was received
+/// within ```timeout```.
+pub fn probe_once(
+    address: SocketAddr,
+    timeout: Duration,
+    time: &dyn TimeSource,
+) -> Result<(Duration, Method), Box<dyn Error>> {
+    match probe_icmp(address.ip(), timeout, time) {
+        Ok(rtt) => Ok((rtt, Method::Icmp)),
+        Err(err) if err.kind() == io::ErrorKind::TimedOut => Err(err.into()),
+        // Usually permission denied, unless ICMP datagram sockets are allowed
+        Err(_) => Ok((probe_tcp(address, timeout, time)?, Method::Tcp)),
+    }
+}
+
+/// Sends an ICMP echo request to ```address``` through an unprivileged
+/// datagram socket and waits for the reply.
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if the socket can't be created,
+/// usually because the user's group isn't allowed to, or an ```Error``` of
+/// kind ```TimedOut``` if no reply was received within ```timeout```.
+pub fn probe_icmp(
+    address: IpAddr,
+    timeout: Duration,
+    time: &dyn TimeSource,
+) -> io::Result<Duration> {
+    let (domain, protocol, request, reply) = match address {
+        IpAddr::V4(_) => (
+            libc::AF_INET,
+            libc::IPPROTO_ICMP,
+            ICMP_ECHO_REQUEST,
+            ICMP_ECHO_REPLY,
+        ),
+        IpAddr::V6(_) => (
+            libc::AF_INET6,
+            libc::IPPROTO_ICMPV6,
+            ICMPV6_ECHO_REQUEST,
+            ICMPV6_ECHO_REPLY,
+        ),
+    };
+
+    let fd = unsafe {
+        libc::socket(domain, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, protocol)
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Closes the socket once dropped
+    let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    let timeval = libc::timeval {
+        tv_sec: timeout.as_secs() as libc::time_t,
+        tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+    };
+    if unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_RCVTIMEO,
+            &timeval as *const libc::timeval as *const libc::c_void,
+            mem::size_of::<libc::timeval>() as libc::socklen_t,
+        )
+    } != 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+
+    // The kernel fills in the identifier and checksum of datagram sockets
+    let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed).to_be_bytes();
+    let packet = [
+        request,
+        0,
+        0,
+        0,
+        0,
+        0,
+        sequence[0],
+        sequence[1],
+        b'd',
+        b'w',
+        b'm',
+        0,
+    ];
+    let (storage, len) = to_sockaddr(address);
+
+    let start = time.instant();
+    if unsafe {
+        libc::sendto(
+            socket.as_raw_fd(),
+            packet.as_ptr() as *const libc::c_void,
+            packet.len(),
+            0,
+            &storage as *const libc::sockaddr_storage as *const libc::sockaddr,
+            len,
+        )
+    } < 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut buffer = [0u8; 128];
+
+    // Each probe has its own socket, whose replies the kernel matches by
+    // identifier, so this only skips replies that aren't echo replies
+    loop {
+        let received = unsafe {
+            libc::recv(
+                socket.as_raw_fd(),
+                buffer.as_mut_ptr() as *mut libc::c_void,
+                buffer.len(),
+                0,
+            )
+        };
+        if received < 0 {
+            let err = io::Error::last_os_error();
+
+            return match err.kind() {
+                io::ErrorKind::WouldBlock => {
+                    Err(io::ErrorKind::TimedOut.into())
+                }
+                _ => Err(err),
+            };
+        }
+
+        let elapsed = time.instant().saturating_duration_since(start);
+
+        if received >= 8 && buffer[0] == reply && buffer[6..8] == sequence {
+            return Ok(elapsed);
+        }
+
+        if elapsed >= timeout {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+    }
+}
+
+/// Returns the time taken to connect to ```address```. A refused connection
+/// counts as a reply, since the host did answer.
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if the host didn't answer
+/// within ```timeout```.
+pub fn probe_tcp(
+    address: SocketAddr,
+    timeout: Duration,
+    time: &dyn TimeSource,
+) -> io::Result<Duration> {
+    let start = time.instant();
+    let result = TcpStream::connect_timeout(&address, timeout);
+    let elapsed = time.instant().saturating_duration_since(start);
+
+    match result {
+        Ok(_) => Ok(elapsed),
+        Err(err) if err.kind() == io::ErrorKind::ConnectionRefused => {
+            Ok(elapsed)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Resolves the given ```host:port``` target to its first address, which
+/// happens before every probe in order to follow DNS changes.
+fn resolve(target: &str) -> Result<SocketAddr, Box<dyn Error>> {
+    target
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| format!("No address found for {}!", target).into())
+}
+
+fn to_sockaddr(address: IpAddr) -> (libc::sockaddr_storage, libc::socklen_t) {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+
+    match address {
+        IpAddr::V4(address) => {
+            let sockaddr = unsafe {
+                &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in)
+            };
+            sockaddr.sin_family = libc::AF_INET as libc::sa_family_t;
+            sockaddr.sin_addr.s_addr = u32::from(address).to_be();
+
+            (
+                storage,
+                mem::size_of::<libc::sockaddr_in>() as libc::socklen_t,
+            )
+        }
+        IpAddr::V6(address) => {
+            let sockaddr = unsafe {
+                &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in6)
+            };
+            sockaddr.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            sockaddr.sin6_addr.s6_addr = address.octets();
+
+            (
+                storage,
+                mem::size_of::<libc::sockaddr_in6>() as libc::socklen_t,
+            )
+        }
+    }
+}
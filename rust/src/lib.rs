@@ -6,33 +6,73 @@
 //! the ```Status``` trait may also be easily used in any applications
 //! that require retrieving system related information.
 
-use std::{error::Error, fmt, process::Command};
+use std::{
+    error::Error,
+    fmt,
+    process::Command,
+    sync::{Condvar, Mutex},
+    time::Duration,
+};
 
 pub mod audio;
 pub mod battery;
+pub mod cgroup;
+pub mod clock;
 pub mod cpu;
-pub mod date;
+pub mod cpufreq;
 pub mod disk;
+pub mod diskio;
+pub mod latency;
+pub mod link;
+pub mod load;
 pub mod memory;
-pub mod time;
+pub mod network;
+pub mod pressure;
+pub mod rfkill;
+pub mod sensors;
+pub mod storage;
+pub mod top;
+pub mod usage;
+pub mod vpn;
 pub mod wifi;
 
+pub mod net;
+pub mod netlink;
+pub mod rate;
 pub mod signals;
+pub mod sysfs;
+pub mod timer;
+pub mod timesource;
 
 pub mod output;
 
 /// Used to easily reference the different structs that implement
 /// the ```Status``` trait in code.
-#[derive(PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum StatusType {
     Audio,
     Battery,
+    Cgroup,
     Cpu,
+    CpuFreq,
     Date,
     Disk,
+    DiskIo,
+    Latency,
+    Link,
+    Load,
     Memory,
+    Network,
+    Pressure,
+    Rfkill,
+    Sensors,
+    Storage,
     Time,
+    Top,
+    Usage,
+    Vpn,
     Wifi,
+    WorldClock,
 }
 
 /// Should be implemented by any struct that aims to be part of
@@ -42,6 +82,50 @@ pub trait Status: fmt::Display + Send {
     /// populated with the most up to date values, and return an ```Error```
     /// otherwise.
     fn update(&mut self) -> Result<(), Box<dyn Error>>;
+
+    /// Should perform the implementor's control action for the given mouse
+    /// ```button``` (1 being left, 2 middle and 3 right), such as cycling
+    /// through values, and return an ```Error``` if the action failed.
+    ///
+    /// Does nothing by default.
+    fn act(&mut self, _button: u8) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+static REFRESH_REQUESTED: Mutex<bool> = Mutex::new(false);
+static REFRESH: Condvar = Condvar::new();
+
+/// Asks for the statuses to be updated and output right away, such as when
+/// a status learns of a change from a background thread.
+pub fn request_refresh() {
+    if let Ok(mut requested) = REFRESH_REQUESTED.lock() {
+        *requested = true;
+        REFRESH.notify_all();
+    }
+}
+
+/// Blocks until either ```request_refresh``` is called or ```timeout```
+/// elapses, waiting indefinitely if it's ```None```, and returns whether a
+/// refresh was requested.
+pub fn wait_for_refresh(timeout: Option<Duration>) -> bool {
+    let requested = match REFRESH_REQUESTED.lock() {
+        Ok(requested) => requested,
+        Err(_) => return false,
+    };
+
+    let requested = match timeout {
+        Some(timeout) => REFRESH
+            .wait_timeout_while(requested, timeout, |requested| !*requested)
+            .map(|(requested, _)| requested)
+            .ok(),
+        None => REFRESH.wait_while(requested, |requested| !*requested).ok(),
+    };
+
+    match requested {
+        Some(mut requested) => std::mem::replace(&mut *requested, false),
+        None => false,
+    }
 }
 
 /// Helper function that runs the ```update``` method on each status returned
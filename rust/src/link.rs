@@ -0,0 +1,164 @@
+//! The ```link``` module provides a struct containing the state of a network
+//! interface's link, such as its operational state, carrier, speed and duplex
+//! found in sysfs, as well as its IP addresses.
+//!
+//! Unless given an interface, the one that currently has the default route is
+//! followed, regardless of whether it's wired or wireless.
+
+use std::{error::Error, net::IpAddr, path::Path};
+
+use crate::{net, sysfs::read_string, Status};
+
+pub struct Link {
+    /// The name of the interface the status is about, if any.
+    pub interface_name: Option<String>,
+    /// The interface's ```operstate```, such as ```up```, ```down```,
+    /// ```dormant``` or ```unknown```.
+    pub operstate: String,
+    pub carrier: bool,
+    pub speed_mbps: Option<u32>,
+    pub duplex: Option<String>,
+    pub wireless: bool,
+    /// The interface's addresses, IPv4 ones first, excluding IPv6 link-local
+    /// addresses.
+    pub addresses: Vec<IpAddr>,
+    interface: Option<String>,
+}
+
+impl Link {
+    /// Follows the interface that currently has the default route.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use dwmstatus::link::Link;
+    ///
+    /// let link_status = Link::new();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/proc/net/route``` can't
+    /// be read.
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        Link::with_interface_name(None)
+    }
+
+    /// Where ```interface_name``` is the name of the interface to follow,
+    /// such as ```enp0s31f6```.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use dwmstatus::link::Link;
+    ///
+    /// let link_status = Link::with_interface("enp0s31f6");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the addresses of the
+    /// interface can't be retrieved.
+    pub fn with_interface(
+        interface_name: &str,
+    ) -> Result<Self, Box<dyn Error>> {
+        Link::with_interface_name(Some(String::from(interface_name)))
+    }
+
+    fn with_interface_name(
+        interface: Option<String>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut link = Link {
+            interface_name: None,
+            operstate: String::new(),
+            carrier: false,
+            speed_mbps: None,
+            duplex: None,
+            wireless: false,
+            addresses: Vec::new(),
+            interface,
+        };
+
+        link.update()?;
+
+        Ok(link)
+    }
+
+    /// Returns whether the interface is up with a carrier. Interfaces such as
+    /// tunnels report an ```unknown``` operational state, which counts as up.
+    pub fn online(&self) -> bool {
+        self.interface_name.is_some()
+            && self.carrier
+            && (self.operstate == "up" || self.operstate == "unknown")
+    }
+
+    pub fn ipv4_address(&self) -> Option<&IpAddr> {
+        self.addresses.iter().find(|address| address.is_ipv4())
+    }
+
+    pub fn ipv6_address(&self) -> Option<&IpAddr> {
+        self.addresses.iter().find(|address| address.is_ipv6())
+    }
+}
+
+impl Status for Link {
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/proc/net/route``` can't
+    /// be read when following the default route, or if the interface's
+    /// addresses can't be retrieved.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let interface_name = match &self.interface {
+            Some(interface) => Some(interface.clone()),
+            None => net::get_default_route_interface()?,
+        };
+        let dir = interface_name
+            .as_ref()
+            .map(|name| Path::new("/sys/class/net").join(name))
+            .filter(|dir| dir.exists());
+
+        let dir = match dir {
+            Some(dir) => dir,
+            None => {
+                self.interface_name = None;
+                self.operstate = String::from("down");
+                self.carrier = false;
+                self.speed_mbps = None;
+                self.duplex = None;
+                self.wireless = false;
+                self.addresses.clear();
+
+                return Ok(());
+            }
+        };
+        let name = interface_name.unwrap_or_default();
+
+        self.operstate = read_string(&dir.join("operstate"))
+            .unwrap_or_else(|_| String::from("unknown"));
+        // Reading the carrier of an interface that's down fails
+        self.carrier = read_string(&dir.join("carrier"))
+            .is_ok_and(|carrier| carrier == "1");
+        // Unknown speeds are reported as -1, or fail to be read altogether
+        self.speed_mbps = read_string(&dir.join("speed"))
+            .ok()
+            .and_then(|speed| speed.parse().ok());
+        self.duplex = read_string(&dir.join("duplex"))
+            .ok()
+            .filter(|duplex| duplex != "unknown");
+        self.wireless = net::is_wireless(&name);
+
+        let mut addresses: Vec<IpAddr> = net::get_addresses(&name)?
+            .into_iter()
+            .filter(|address| match address {
+                IpAddr::V4(_) => true,
+                IpAddr::V6(address) => address.segments()[0] & 0xffc0 != 0xfe80,
+            })
+            .collect();
+        addresses.sort_by_key(|address| address.is_ipv6());
+
+        self.addresses = addresses;
+        self.interface_name = Some(name);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+//! The ```load``` module provides a struct containing information related to
+//! the system's load, such as the 1, 5 and 15 minute load averages as well as
+//! the number of running and total tasks.
+
+use std::{error::Error, fs};
+
+use crate::Status;
+
+pub struct Load {
+    pub one_minute: f32,
+    pub five_minutes: f32,
+    pub fifteen_minutes: f32,
+    pub running_tasks: u32,
+    pub total_tasks: u32,
+    pub cpu_count: u32,
+    pub normalized: bool,
+}
+
+impl Load {
+    /// Where ```normalized``` determines whether the load averages should be
+    /// displayed divided by the number of online cpus, so that ```1.0```
+    /// means every cpu is busy regardless of the machine.
+    pub fn new(normalized: bool) -> Self {
+        let cpu_count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+
+        Load {
+            one_minute: 0.0,
+            five_minutes: 0.0,
+            fifteen_minutes: 0.0,
+            running_tasks: 0,
+            total_tasks: 0,
+            cpu_count: cpu_count.max(1) as u32,
+            normalized,
+        }
+    }
+
+    /// Returns the 1, 5 and 15 minute load averages, divided by the number of
+    /// online cpus if the struct was created as ```normalized```.
+    pub fn averages(&self) -> (f32, f32, f32) {
+        let divisor = if self.normalized {
+            self.cpu_count as f32
+        } else {
+            1.0
+        };
+
+        (
+            self.one_minute / divisor,
+            self.five_minutes / divisor,
+            self.fifteen_minutes / divisor,
+        )
+    }
+
+    /// Updates the struct's fields using the given ```/proc/loadavg```
+    /// contents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dwmstatus::load::Load;
+    ///
+    /// let mut load = Load::new(true);
+    /// load.cpu_count = 4;
+    /// load.update_from_loadavg("2.00 1.50 0.50 3/1234 56789\n")?;
+    ///
+    /// assert_eq!(load.running_tasks, 3);
+    /// assert_eq!(load.total_tasks, 1234);
+    /// assert_eq!(load.averages(), (0.5, 0.375, 0.125));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```contents``` isn't in the
+    /// expected format.
+    pub fn update_from_loadavg(
+        &mut self,
+        contents: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut values = contents.split_whitespace();
+        let mut next =
+            || values.next().ok_or("Unexpected /proc/loadavg format!");
+
+        self.one_minute = next()?.parse()?;
+        self.five_minutes = next()?.parse()?;
+        self.fifteen_minutes = next()?.parse()?;
+
+        let (running, total) = next()?
+            .split_once('/')
+            .ok_or("Unexpected /proc/loadavg format!")?;
+
+        self.running_tasks = running.parse()?;
+        self.total_tasks = total.parse()?;
+
+        Ok(())
+    }
+}
+
+impl Status for Load {
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/proc/loadavg``` can't
+    /// be opened for reading or its contents can't be parsed.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        self.update_from_loadavg(&fs::read_to_string("/proc/loadavg")?)
+    }
+}
@@ -1,10 +1,12 @@
 use std::{
     collections::HashMap,
     io::Error,
-    sync::{Arc, Mutex},
-    sync::mpsc::Receiver,
+    os::raw::c_int,
     sync::mpsc,
+    sync::mpsc::Receiver,
+    sync::{Arc, Mutex},
     thread,
+    time::Duration,
 };
 
 use signal_hook::consts::signal::*;
@@ -12,39 +14,61 @@ use signal_hook::consts::signal::*;
 use dwmstatus::*;
 
 fn main() -> Result<(), Error> {
-    let index_map = get_status_to_index_map();
-    let statuses = Arc::new(Mutex::new(get_statuses(&index_map)));
+    let (index_map, statuses) = get_statuses(&get_status_to_index_map());
+    let statuses = Arc::new(Mutex::new(statuses));
 
     let cloned_statuses = statuses.clone();
+    let update_period = get_update_period(&index_map);
     let (tx, rx) = mpsc::channel();
-    let app = thread::spawn(move || run_app(cloned_statuses, rx));
+    let app =
+        thread::spawn(move || run_app(cloned_statuses, rx, update_period));
 
-    let audio_status_index = *index_map.get(&StatusType::Audio).unwrap();
+    let audio_status_index = index_map.get(&StatusType::Audio).copied();
 
-    let mut signals = signals::get_signals()?;
+    let action_map = get_signal_to_action_map();
+    let action_signals: Vec<c_int> = action_map.keys().copied().collect();
+
+    let mut signals = signals::get_signals(&action_signals)?;
     for info in &mut signals {
         match info.signal {
             SIGUSR1 => {
                 let mut statuses = statuses.lock().unwrap();
 
-                if let Err(err) = statuses[audio_status_index].update() {
-                    eprintln!("{}", err);
+                if let Some(index) = audio_status_index {
+                    if let Err(err) = statuses[index].update() {
+                        eprintln!("{}", err);
+                    }
                 }
                 output::output_statuses(statuses.iter());
-            },
+            }
+            signal if action_map.contains_key(&signal) => {
+                let (status_type, button) = &action_map[&signal];
+
+                if let Some(index) = index_map.get(status_type) {
+                    let mut statuses = statuses.lock().unwrap();
+
+                    if let Err(err) = statuses[*index].act(*button) {
+                        eprintln!("{}", err);
+                    }
+                    output::output_statuses(statuses.iter());
+                }
+            }
             _ => break,
         }
     }
 
     tx.send(0).unwrap();
+    request_refresh();
     app.join().unwrap();
 
     Ok(())
 }
 
-fn run_app(statuses: Arc<Mutex<Vec<Box<dyn Status>>>>, rx: Receiver<u8>) {
-    let sleep_time = std::time::Duration::from_millis(1000);
-
+fn run_app(
+    statuses: Arc<Mutex<Vec<Box<dyn Status>>>>,
+    rx: Receiver<u8>,
+    update_period: Option<Duration>,
+) {
     loop {
         let mut statuses = statuses.lock().unwrap();
 
@@ -53,7 +77,7 @@ fn run_app(statuses: Arc<Mutex<Vec<Box<dyn Status>>>>, rx: Receiver<u8>) {
 
         drop(statuses);
 
-        thread::sleep(sleep_time);
+        wait_for_refresh(update_period);
 
         match rx.try_recv() {
             Ok(_) => break,
@@ -62,6 +86,25 @@ fn run_app(statuses: Arc<Mutex<Vec<Box<dyn Status>>>>, rx: Receiver<u8>) {
     }
 }
 
+/// Returns the period at which statuses are updated, or ```None``` when every
+/// status requests its own refreshes, such as clocks waking on the boundaries
+/// of minutes.
+fn get_update_period(
+    index_map: &HashMap<StatusType, usize>,
+) -> Option<Duration> {
+    let self_refreshing =
+        [StatusType::Date, StatusType::Time, StatusType::WorldClock];
+
+    if index_map
+        .keys()
+        .all(|status_type| self_refreshing.contains(status_type))
+    {
+        None
+    } else {
+        Some(Duration::from_millis(1000))
+    }
+}
+
 fn get_status_to_index_map() -> HashMap<StatusType, usize> {
     HashMap::from([
         (StatusType::Wifi, 0),
@@ -72,34 +115,104 @@ fn get_status_to_index_map() -> HashMap<StatusType, usize> {
         (StatusType::Battery, 5),
         (StatusType::Date, 6),
         (StatusType::Time, 7),
+        (StatusType::CpuFreq, 8),
+        (StatusType::Sensors, 9),
+        (StatusType::Load, 10),
+        (StatusType::Pressure, 11),
+        (StatusType::Cgroup, 12),
+        (StatusType::Top, 13),
+        (StatusType::DiskIo, 14),
+        (StatusType::Storage, 15),
+        (StatusType::Network, 16),
+        (StatusType::Link, 17),
+        (StatusType::Vpn, 18),
+        (StatusType::Usage, 19),
+        (StatusType::Rfkill, 20),
+        (StatusType::WorldClock, 21),
     ])
 }
 
-fn get_statuses(index_map: &HashMap<StatusType, usize>) -> Vec<Box<dyn Status>> {
-    let mut index_map: Vec<(&StatusType, &usize)> = index_map.iter().collect();
-
-    index_map.sort_by_key(|(_, index)| *index);
-    index_map
-        .iter()
-        .map(|(status_type, _)| -> Box<dyn Status> {
-            match status_type {
-                StatusType::Audio => Box::new(
-                    audio::Audio::new("default", "Master").expect("Failed to create Audio status"),
-                ),
-                StatusType::Battery => {
-                    Box::new(battery::Battery::new().expect("Failed to create Battery status"))
-                }
-                StatusType::Cpu => Box::new(cpu::Cpu::new()),
-                StatusType::Date => Box::new(date::Date::new()),
-                StatusType::Disk => {
-                    Box::new(disk::Disk::new("/").expect("Failed to create Disk status"))
-                }
-                StatusType::Memory => Box::new(memory::Memory::new()),
-                StatusType::Time => Box::new(time::Time::new()),
-                StatusType::Wifi => {
-                    Box::new(wifi::Wifi::new("wlp4s0").expect("Failed to create Wifi status"))
-                }
+/// Maps the signals that trigger a status' action, such as those sent by
+/// ```pkill -RTMIN+1 dwmstatus```, to the status and mouse button in question.
+/// Signals for statuses missing from the status to index map are ignored.
+fn get_signal_to_action_map() -> HashMap<c_int, (StatusType, u8)> {
+    HashMap::from([
+        (signals::action_signal(1), (StatusType::CpuFreq, 1)),
+        (signals::action_signal(2), (StatusType::CpuFreq, 3)),
+        (signals::action_signal(3), (StatusType::Rfkill, 1)),
+        (signals::action_signal(4), (StatusType::Rfkill, 3)),
+        (signals::action_signal(5), (StatusType::Time, 1)),
+        (signals::action_signal(6), (StatusType::Date, 3)),
+        (signals::action_signal(7), (StatusType::WorldClock, 2)),
+    ])
+}
+
+/// Creates the statuses of the given status to index map in order, and
+/// returns them along with the index of each status that could be created.
+/// Statuses that couldn't, such as when the hardware or kernel feature they
+/// report on is missing, are left out.
+fn get_statuses(
+    index_map: &HashMap<StatusType, usize>,
+) -> (HashMap<StatusType, usize>, Vec<Box<dyn Status>>) {
+    let mut status_types: Vec<(&StatusType, &usize)> =
+        index_map.iter().collect();
+    let mut created_index_map = HashMap::new();
+    let mut statuses = Vec::new();
+
+    status_types.sort_by_key(|(_, index)| *index);
+    for (status_type, _) in status_types {
+        match get_status(status_type) {
+            Ok(status) => {
+                created_index_map.insert(*status_type, statuses.len());
+                statuses.push(status);
             }
-        })
-        .collect()
+            Err(err) => {
+                eprintln!("Failed to create {:?} status: {}", status_type, err)
+            }
+        }
+    }
+
+    (created_index_map, statuses)
+}
+
+fn get_status(
+    status_type: &StatusType,
+) -> Result<Box<dyn Status>, Box<dyn std::error::Error>> {
+    Ok(match status_type {
+        StatusType::Audio => Box::new(audio::Audio::new("default", "Master")?),
+        StatusType::Battery => Box::new(battery::Battery::new()?),
+        StatusType::Cgroup => Box::new(cgroup::Cgroup::current()?),
+        StatusType::Cpu => Box::new(cpu::Cpu::new()?),
+        StatusType::CpuFreq => Box::new(cpufreq::CpuFreq::new()?),
+        StatusType::Date => Box::new(clock::Clock::new("%A %x")?),
+        StatusType::Disk => Box::new(disk::Disk::new(&["/"])?),
+        StatusType::DiskIo => Box::new(diskio::DiskIo::new(None)?),
+        // Not displayed unless given an index, since it probes an outside host
+        StatusType::Latency => Box::new(latency::Latency::new(
+            "1.1.1.1",
+            443,
+            Duration::from_secs(5),
+        )?),
+        StatusType::Link => Box::new(link::Link::new()?),
+        StatusType::Load => Box::new(load::Load::new(false)),
+        StatusType::Memory => Box::new(memory::Memory::new()),
+        StatusType::Network => Box::new(network::Network::new(None)?),
+        StatusType::Pressure => Box::new(pressure::Pressure::new()?),
+        StatusType::Rfkill => Box::new(rfkill::Rfkill::new()?),
+        StatusType::Sensors => Box::new(sensors::Sensors::cpu()?),
+        StatusType::Storage => Box::new(storage::Storage::new()?),
+        StatusType::Time => Box::new(clock::Clock::new("%I:%M %p")?),
+        StatusType::Top => {
+            Box::new(top::Top::new(&["dwmstatus"], Duration::from_secs(5))?)
+        }
+        StatusType::Usage => Box::new(usage::Usage::new(None)?),
+        StatusType::Vpn => Box::new(vpn::Vpn::new(None, false)?),
+        StatusType::Wifi => Box::new(wifi::Wifi::new()?),
+        StatusType::WorldClock => Box::new(clock::Clock::with_zones(
+            "%H:%M",
+            &[("NYC", "America/New_York"), ("BER", "Europe/Berlin")],
+            true,
+            Arc::new(timesource::SystemTimeSource),
+        )?),
+    })
 }
@@ -1,53 +1,209 @@
 //! The ```memory``` module provides a struct containing information related to
-//! the system's RAM modules, such as the number of free and total kibibytes.
+//! the system's RAM modules and swap, such as the number of available, cached
+//! and total kibibytes, as well as the usage of compressed swap.
 
-use std::{error::Error, fs};
+use std::{collections::HashMap, error::Error, fs};
 
-use crate::Status;
+use crate::{output::Units, Status};
 
 pub struct Memory {
-    pub available_kibibytes: u32,
-    pub total_kibibytes: u32,
+    pub total_kibibytes: u64,
+    pub available_kibibytes: u64,
+    pub free_kibibytes: u64,
+    pub buffers_kibibytes: u64,
+    pub cached_kibibytes: u64,
+    pub shmem_kibibytes: u64,
+    pub dirty_kibibytes: u64,
+    pub writeback_kibibytes: u64,
+    pub swap_total_kibibytes: u64,
+    pub swap_free_kibibytes: u64,
+    /// The memory used by the zswap pool, which holds the ```zswapped```
+    /// pages in compressed form.
+    pub zswap_kibibytes: u64,
+    pub zswapped_kibibytes: u64,
+    pub zram: Zram,
+    /// The units used when displaying human readable sizes.
+    pub units: Units,
+}
+
+/// The combined usage of every zram device, as found in their ```mm_stat```.
+#[derive(Clone, Copy, Default)]
+pub struct Zram {
+    pub original_bytes: u64,
+    pub compressed_bytes: u64,
+    pub used_bytes: u64,
 }
 
 impl Memory {
     pub fn new() -> Self {
         Memory {
-            available_kibibytes: 0,
             total_kibibytes: 0,
+            available_kibibytes: 0,
+            free_kibibytes: 0,
+            buffers_kibibytes: 0,
+            cached_kibibytes: 0,
+            shmem_kibibytes: 0,
+            dirty_kibibytes: 0,
+            writeback_kibibytes: 0,
+            swap_total_kibibytes: 0,
+            swap_free_kibibytes: 0,
+            zswap_kibibytes: 0,
+            zswapped_kibibytes: 0,
+            zram: Zram::default(),
+            units: Units::Iec,
         }
     }
+
+    pub fn used_kibibytes(&self) -> u64 {
+        self.total_kibibytes
+            .saturating_sub(self.available_kibibytes)
+    }
+
+    pub fn swap_used_kibibytes(&self) -> u64 {
+        self.swap_total_kibibytes
+            .saturating_sub(self.swap_free_kibibytes)
+    }
+
+    /// Updates the struct's fields, except for ```zram```, using the
+    /// given ```/proc/meminfo``` contents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dwmstatus::memory::Memory;
+    ///
+    /// let mut memory = Memory::new();
+    /// memory.update_from_meminfo(
+    ///     "MemTotal:       16303428 kB\n\
+    ///      MemFree:         1249968 kB\n\
+    ///      Buffers:          520004 kB\n\
+    ///      Cached:          6201448 kB\n\
+    ///      MemAvailable:    9412064 kB\n\
+    ///      SwapTotal:       8388604 kB\n\
+    ///      SwapFree:        8126460 kB\n\
+    ///      HugePages_Total:       0\n",
+    /// )?;
+    ///
+    /// assert_eq!(memory.available_kibibytes, 9412064);
+    /// assert_eq!(memory.swap_used_kibibytes(), 262144);
+    /// assert_eq!(memory.shmem_kibibytes, 0);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```contents``` can't be
+    /// parsed or doesn't contain ```MemTotal```.
+    pub fn update_from_meminfo(
+        &mut self,
+        contents: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let meminfo = parse_meminfo(contents)?;
+        let value = |key: &str| meminfo.get(key).copied().unwrap_or(0);
+
+        self.total_kibibytes = *meminfo
+            .get("MemTotal")
+            .ok_or("No MemTotal in /proc/meminfo!")?;
+        self.free_kibibytes = value("MemFree");
+        self.buffers_kibibytes = value("Buffers");
+        self.cached_kibibytes = value("Cached");
+        // Kernels prior to 3.14 don't provide an estimate of available memory
+        self.available_kibibytes =
+            meminfo.get("MemAvailable").copied().unwrap_or(
+                self.free_kibibytes
+                    + self.buffers_kibibytes
+                    + self.cached_kibibytes,
+            );
+        self.shmem_kibibytes = value("Shmem");
+        self.dirty_kibibytes = value("Dirty");
+        self.writeback_kibibytes = value("Writeback");
+        self.swap_total_kibibytes = value("SwapTotal");
+        self.swap_free_kibibytes = value("SwapFree");
+        self.zswap_kibibytes = value("Zswap");
+        self.zswapped_kibibytes = value("Zswapped");
+
+        Ok(())
+    }
 }
 
 impl Status for Memory {
     /// # Errors
     ///
     /// This method will return an ```Error``` if ```/proc/meminfo``` can't
-    /// be opened for reading.
+    /// be opened for reading or parsed.
     fn update(&mut self) -> Result<(), Box<dyn Error>> {
-        let contents = fs::read_to_string("/proc/meminfo")?;
-        let mut lines = contents.lines();
+        self.update_from_meminfo(&fs::read_to_string("/proc/meminfo")?)?;
+        self.zram = read_zram()?;
 
-        self.total_kibibytes = lines
-            .next()
-            .unwrap()
+        Ok(())
+    }
+}
+
+/// Parses the given ```/proc/meminfo``` contents into a map of each key to its
+/// value, which is in kibibytes for every key with a unit.
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if any of the lines isn't in
+/// the ```Key: value``` format.
+pub fn parse_meminfo(
+    contents: &str,
+) -> Result<HashMap<String, u64>, Box<dyn Error>> {
+    let mut meminfo = HashMap::new();
+
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let (key, value) = line
+            .split_once(':')
+            .ok_or(format!("Unexpected /proc/meminfo line: '{}'", line))?;
+        let value = value
             .split_whitespace()
-            .skip(1)
-            .next()
-            .unwrap()
-            .parse()
-            .unwrap();
-        self.available_kibibytes = lines
-            .skip(1)
             .next()
-            .unwrap()
+            .ok_or(format!("Unexpected /proc/meminfo line: '{}'", line))?;
+
+        meminfo.insert(String::from(key), value.parse()?);
+    }
+
+    Ok(meminfo)
+}
+
+fn read_zram() -> Result<Zram, Box<dyn Error>> {
+    let mut zram = Zram::default();
+
+    let entries = match fs::read_dir("/sys/block") {
+        Ok(entries) => entries,
+        Err(_) => return Ok(zram),
+    };
+
+    for entry in entries {
+        let entry = entry?;
+
+        if !entry.file_name().to_string_lossy().starts_with("zram") {
+            continue;
+        }
+        let path = entry.path();
+
+        // Devices that haven't been initialized yet can't report their usage
+        let contents = match fs::read_to_string(path.join("mm_stat")) {
+            Ok(contents) => contents,
+            Err(_) => continue,
+        };
+        let values = contents
             .split_whitespace()
-            .skip(1)
-            .next()
-            .unwrap()
-            .parse()
-            .unwrap();
+            .map(|value| value.parse())
+            .collect::<Result<Vec<u64>, _>>()?;
 
-        Ok(())
+        if values.len() < 3 {
+            return Err(format!(
+                "Unexpected mm_stat contents: '{}'",
+                contents.trim()
+            )
+            .into());
+        }
+
+        zram.original_bytes += values[0];
+        zram.compressed_bytes += values[1];
+        zram.used_bytes += values[2];
     }
+
+    Ok(zram)
 }
@@ -0,0 +1,199 @@
+//! The ```net``` module provides helper functions shared by the network
+//! related modules, such as finding the system's wireless interfaces or the
+//! interface that currently has the default route.
+
+use std::{
+    error::Error,
+    ffi::CStr,
+    fs, io, mem,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::Path,
+    ptr, slice,
+};
+
+/// The ```RTF_UP``` flag of ```/proc/net/route```'s routes.
+static ROUTE_UP_FLAG: u32 = 0x1;
+
+/// Returns the names of the interfaces found in ```/sys/class/net```, sorted.
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if ```/sys/class/net``` can't be
+/// read.
+pub fn get_interfaces() -> Result<Vec<String>, Box<dyn Error>> {
+    let mut interfaces = fs::read_dir("/sys/class/net")?
+        .map(|entry| {
+            entry.map(|entry| entry.file_name().to_string_lossy().into_owned())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    interfaces.sort();
+
+    Ok(interfaces)
+}
+
+/// Returns the names of the wireless interfaces, which are the ones exposing
+/// either a ```wireless``` directory or a ```phy80211``` link in sysfs.
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if ```/sys/class/net``` can't be
+/// read.
+pub fn get_wireless_interfaces() -> Result<Vec<String>, Box<dyn Error>> {
+    Ok(get_interfaces()?
+        .into_iter()
+        .filter(|interface| is_wireless(interface))
+        .collect())
+}
+
+/// Returns whether the given interface exposes either a ```wireless```
+/// directory or a ```phy80211``` link in sysfs.
+pub fn is_wireless(interface: &str) -> bool {
+    let dir = Path::new("/sys/class/net").join(interface);
+
+    dir.join("wireless").exists() || dir.join("phy80211").exists()
+}
+
+/// Returns the IPv4 and IPv6 addresses assigned to the given interface, as
+/// found by ```getifaddrs```.
+///
+/// # Examples
+///
+/// ```no_run
+/// use dwmstatus::net::get_addresses;
+///
+/// let addresses = get_addresses("wlp4s0")?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+///
+/// This function will return an ```Error``` containing the ```errno```
+/// description if the ```getifaddrs``` call fails.
+pub fn get_addresses(interface: &str) -> Result<Vec<IpAddr>, Box<dyn Error>> {
+    let mut ifaddrs: *mut libc::ifaddrs = ptr::null_mut();
+
+    if unsafe { libc::getifaddrs(&mut ifaddrs) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+
+    let mut addresses = Vec::new();
+    let mut current = ifaddrs;
+
+    while !current.is_null() {
+        let entry = unsafe { &*current };
+        current = entry.ifa_next;
+
+        if entry.ifa_addr.is_null()
+            || unsafe { CStr::from_ptr(entry.ifa_name) }.to_bytes()
+                != interface.as_bytes()
+        {
+            continue;
+        }
+
+        let len = match unsafe { (*entry.ifa_addr).sa_family } as libc::c_int {
+            libc::AF_INET => mem::size_of::<libc::sockaddr_in>(),
+            libc::AF_INET6 => mem::size_of::<libc::sockaddr_in6>(),
+            _ => continue,
+        };
+        let bytes =
+            unsafe { slice::from_raw_parts(entry.ifa_addr as *const u8, len) };
+
+        addresses.extend(parse_sockaddr(bytes));
+    }
+
+    unsafe { libc::freeifaddrs(ifaddrs) };
+
+    Ok(addresses)
+}
+
+/// Parses the address of the given ```sockaddr_in``` or ```sockaddr_in6```
+/// bytes, or returns ```None``` for any other address family.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+/// use dwmstatus::net::parse_sockaddr;
+///
+/// let mut ipv4 = [0u8; 16];
+/// ipv4[..2].copy_from_slice(&(libc::AF_INET as u16).to_ne_bytes());
+/// ipv4[2..4].copy_from_slice(&80u16.to_be_bytes());
+/// ipv4[4..8].copy_from_slice(&[192, 168, 1, 20]);
+///
+/// let mut ipv6 = [0u8; 28];
+/// ipv6[..2].copy_from_slice(&(libc::AF_INET6 as u16).to_ne_bytes());
+/// ipv6[8..24].copy_from_slice(&Ipv6Addr::LOCALHOST.octets());
+///
+/// assert_eq!(
+///     parse_sockaddr(&ipv4),
+///     Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 20))),
+/// );
+/// assert_eq!(parse_sockaddr(&ipv6), Some(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+/// assert_eq!(parse_sockaddr(&ipv6[..16]), None);
+/// assert_eq!(parse_sockaddr(&[0u8; 16]), None);
+/// ```
+pub fn parse_sockaddr(bytes: &[u8]) -> Option<IpAddr> {
+    let family = u16::from_ne_bytes([*bytes.first()?, *bytes.get(1)?]);
+
+    match family as libc::c_int {
+        libc::AF_INET => {
+            let octets: [u8; 4] = bytes.get(4..8)?.try_into().ok()?;
+
+            Some(IpAddr::V4(Ipv4Addr::from(octets)))
+        }
+        libc::AF_INET6 => {
+            let octets: [u8; 16] = bytes.get(8..24)?.try_into().ok()?;
+
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the name of the interface that currently has the default route,
+/// if any.
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if ```/proc/net/route``` can't be
+/// read.
+pub fn get_default_route_interface() -> Result<Option<String>, Box<dyn Error>> {
+    Ok(parse_default_route(&fs::read_to_string("/proc/net/route")?))
+}
+
+/// Returns the interface of the default route with the lowest metric found in
+/// the given ```/proc/net/route``` contents, skipping any malformed lines.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::net::parse_default_route;
+///
+/// let contents = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+///                 enp0s31f6\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0\n\
+///                 wlp4s0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0\n\
+///                 wlp4s0\t0001A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\t0\t0\t0\n";
+///
+/// assert_eq!(parse_default_route(contents), Some(String::from("enp0s31f6")));
+/// ```
+pub fn parse_default_route(contents: &str) -> Option<String> {
+    contents
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let flags = u32::from_str_radix(fields.get(3)?, 16).ok()?;
+            let metric: u32 = fields.get(6)?.parse().ok()?;
+
+            if *fields.get(1)? != "00000000" || *fields.get(7)? != "00000000" {
+                return None;
+            }
+            if flags & ROUTE_UP_FLAG == 0 {
+                return None;
+            }
+
+            Some((metric, String::from(fields[0])))
+        })
+        .min_by_key(|(metric, _)| *metric)
+        .map(|(_, interface)| interface)
+}
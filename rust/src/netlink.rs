@@ -0,0 +1,471 @@
+//! The ```netlink``` module provides a minimal generic netlink client, used to
+//! query kernel subsystems such as nl80211 which expose information that can't
+//! be found in ```/proc``` or ```/sys```.
+//!
+//! Messages are encoded and parsed separately from the socket itself, so that
+//! parsing may be tested against captured messages.
+
+use std::{error::Error, io, mem, os::raw::c_int};
+
+static MESSAGE_HEADER_LEN: usize = 16;
+static GENERIC_HEADER_LEN: usize = 4;
+static ATTRIBUTE_HEADER_LEN: usize = 4;
+
+static NLMSG_ERROR: u16 = 2;
+static NLMSG_DONE: u16 = 3;
+static NLM_F_REQUEST: u16 = 0x1;
+static NLM_F_MULTI: u16 = 0x2;
+static NLM_F_DUMP: u16 = 0x300;
+
+/// The nested and byte order flags, which aren't part of an attribute's type.
+static NLA_TYPE_MASK: u16 = 0x3fff;
+
+static GENL_ID_CTRL: u16 = 0x10;
+static CTRL_CMD_GETFAMILY: u8 = 3;
+static CTRL_ATTR_FAMILY_ID: u16 = 1;
+static CTRL_ATTR_FAMILY_NAME: u16 = 2;
+
+static RECEIVE_BUFFER_LEN: usize = 64 * 1024;
+
+/// A netlink attribute, whose payload is interpreted according to its type.
+pub struct Attribute<'a> {
+    pub kind: u16,
+    pub payload: &'a [u8],
+}
+
+/// A generic netlink message, without its netlink header.
+pub struct GenericMessage<'a> {
+    pub command: u8,
+    pub attributes: Vec<Attribute<'a>>,
+}
+
+/// The payloads of netlink messages, along with whether the reply they're
+/// part of is complete.
+pub struct Replies<'a> {
+    pub payloads: Vec<&'a [u8]>,
+    pub done: bool,
+}
+
+/// A generic netlink socket, whose requests time out after a second so that
+/// an unresponsive subsystem can't hang the status bar.
+pub struct Socket {
+    fd: c_int,
+    sequence: u32,
+}
+
+impl<'a> Attribute<'a> {
+    pub fn u8(&self) -> Option<u8> {
+        self.payload.first().copied()
+    }
+
+    pub fn u16(&self) -> Option<u16> {
+        Some(u16::from_ne_bytes(self.payload.get(..2)?.try_into().ok()?))
+    }
+
+    pub fn u32(&self) -> Option<u32> {
+        Some(u32::from_ne_bytes(self.payload.get(..4)?.try_into().ok()?))
+    }
+
+    pub fn u64(&self) -> Option<u64> {
+        Some(u64::from_ne_bytes(self.payload.get(..8)?.try_into().ok()?))
+    }
+
+    /// Returns the payload as a string, without its nul terminator.
+    pub fn string(&self) -> String {
+        let payload = self.payload.strip_suffix(&[0]).unwrap_or(self.payload);
+
+        String::from_utf8_lossy(payload).into_owned()
+    }
+
+    /// Returns the attributes nested within the payload.
+    pub fn nested(&self) -> Vec<Attribute<'a>> {
+        parse_attributes(self.payload)
+    }
+}
+
+impl<'a> GenericMessage<'a> {
+    /// Where ```payload``` is a netlink message's payload, starting with the
+    /// generic netlink header.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```payload``` is too short
+    /// to contain a generic netlink header.
+    pub fn parse(payload: &'a [u8]) -> Result<Self, Box<dyn Error>> {
+        if payload.len() < GENERIC_HEADER_LEN {
+            return Err("Truncated generic netlink message!".into());
+        }
+
+        Ok(GenericMessage {
+            command: payload[0],
+            attributes: parse_attributes(&payload[GENERIC_HEADER_LEN..]),
+        })
+    }
+
+    /// Returns the first top level attribute of the given type, if any.
+    pub fn attribute(&self, kind: u16) -> Option<&Attribute<'a>> {
+        find_attribute(&self.attributes, kind)
+    }
+}
+
+impl Socket {
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` containing the ```errno```
+    /// description if the socket can't be created or bound.
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                libc::NETLINK_GENERIC,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        // Closes the file descriptor should anything below fail
+        let socket = Socket { fd, sequence: 0 };
+
+        let mut address: libc::sockaddr_nl = unsafe { mem::zeroed() };
+        address.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+
+        let timeout = libc::timeval {
+            tv_sec: 1,
+            tv_usec: 0,
+        };
+
+        unsafe {
+            if libc::bind(
+                fd,
+                &address as *const libc::sockaddr_nl as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            ) != 0
+            {
+                return Err(io::Error::last_os_error().into());
+            }
+
+            if libc::setsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_RCVTIMEO,
+                &timeout as *const libc::timeval as *const libc::c_void,
+                mem::size_of::<libc::timeval>() as libc::socklen_t,
+            ) != 0
+            {
+                return Err(io::Error::last_os_error().into());
+            }
+        }
+
+        Ok(socket)
+    }
+
+    /// Returns the identifier of the generic netlink family with the given
+    /// ```name```, such as ```nl80211``` or ```wireguard```.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the family isn't registered,
+    /// usually because its kernel module isn't loaded.
+    pub fn resolve_family(
+        &mut self,
+        name: &str,
+    ) -> Result<u16, Box<dyn Error>> {
+        let mut family_name = name.as_bytes().to_vec();
+        family_name.push(0);
+
+        let replies = self.request(
+            GENL_ID_CTRL,
+            CTRL_CMD_GETFAMILY,
+            1,
+            false,
+            &encode_attribute(CTRL_ATTR_FAMILY_NAME, &family_name),
+        )?;
+
+        for reply in &replies {
+            let message = GenericMessage::parse(reply)?;
+
+            if let Some(id) = message
+                .attribute(CTRL_ATTR_FAMILY_ID)
+                .and_then(|id| id.u16())
+            {
+                return Ok(id);
+            }
+        }
+
+        Err(format!("No {} generic netlink family found!", name).into())
+    }
+
+    /// Sends a request for the given ```command``` of the given ```family```
+    /// along with the already encoded ```attributes```, and returns the
+    /// payloads of every reply, which may be parsed using
+    /// ```GenericMessage::parse```.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the request can't be sent,
+    /// no reply is received in time, or the kernel replies with an error.
+    pub fn request(
+        &mut self,
+        family: u16,
+        command: u8,
+        version: u8,
+        dump: bool,
+        attributes: &[u8],
+    ) -> Result<Vec<Vec<u8>>, Box<dyn Error>> {
+        self.sequence = self.sequence.wrapping_add(1);
+
+        let flags = if dump {
+            NLM_F_REQUEST | NLM_F_DUMP
+        } else {
+            NLM_F_REQUEST
+        };
+        let request = encode_request(
+            family,
+            flags,
+            self.sequence,
+            command,
+            version,
+            attributes,
+        );
+
+        let sent = unsafe {
+            libc::send(
+                self.fd,
+                request.as_ptr() as *const libc::c_void,
+                request.len(),
+                0,
+            )
+        };
+        if sent < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        let mut payloads = Vec::new();
+        let mut buffer = vec![0u8; RECEIVE_BUFFER_LEN];
+
+        loop {
+            let received = unsafe {
+                libc::recv(
+                    self.fd,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    buffer.len(),
+                    0,
+                )
+            };
+            if received < 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+
+            let replies =
+                parse_messages(&buffer[..received as usize], self.sequence)?;
+            payloads.extend(
+                replies.payloads.into_iter().map(|payload| payload.to_vec()),
+            );
+
+            if replies.done {
+                return Ok(payloads);
+            }
+        }
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+/// Parses the attributes found in ```bytes```, stopping at the first
+/// malformed one.
+pub fn parse_attributes(bytes: &[u8]) -> Vec<Attribute<'_>> {
+    let mut attributes = Vec::new();
+    let mut rest = bytes;
+
+    while rest.len() >= ATTRIBUTE_HEADER_LEN {
+        let len = u16::from_ne_bytes([rest[0], rest[1]]) as usize;
+        let kind = u16::from_ne_bytes([rest[2], rest[3]]) & NLA_TYPE_MASK;
+
+        if len < ATTRIBUTE_HEADER_LEN || len > rest.len() {
+            break;
+        }
+
+        attributes.push(Attribute {
+            kind,
+            payload: &rest[ATTRIBUTE_HEADER_LEN..len],
+        });
+        rest = &rest[align(len).min(rest.len())..];
+    }
+
+    attributes
+}
+
+/// Returns the first attribute of the given type, if any.
+pub fn find_attribute<'a, 'b>(
+    attributes: &'b [Attribute<'a>],
+    kind: u16,
+) -> Option<&'b Attribute<'a>> {
+    attributes.iter().find(|attribute| attribute.kind == kind)
+}
+
+/// Splits the netlink messages found in ```bytes``` into their payloads. The
+/// reply is complete once a dump is done, or when it isn't part of a dump at
+/// all.
+///
+/// Messages whose sequence number isn't ```sequence```, such as late replies
+/// to earlier requests that timed out, are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::netlink::{parse_messages, GenericMessage};
+///
+/// let bytes = [
+///     // A nl80211 interface, holding its index
+///     0x1c, 0x00, 0x00, 0x00, 0x1c, 0x00, 0x02, 0x00,
+///     0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+///     0x07, 0x01, 0x00, 0x00,
+///     0x08, 0x00, 0x03, 0x00, 0x03, 0x00, 0x00, 0x00,
+///     // The end of the dump
+///     0x14, 0x00, 0x00, 0x00, 0x03, 0x00, 0x02, 0x00,
+///     0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+///     0x00, 0x00, 0x00, 0x00,
+/// ];
+///
+/// let replies = parse_messages(&bytes, 1)?;
+/// assert!(replies.done);
+/// assert_eq!(replies.payloads.len(), 1);
+///
+/// let message = GenericMessage::parse(replies.payloads[0])?;
+/// assert_eq!(message.command, 7);
+/// assert_eq!(message.attribute(3).and_then(|index| index.u32()), Some(3));
+///
+/// // A reply to an earlier request
+/// let replies = parse_messages(&bytes, 2)?;
+/// assert!(!replies.done);
+/// assert!(replies.payloads.is_empty());
+///
+/// // No such device
+/// let error = [
+///     0x24, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00,
+///     0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+///     0xed, 0xff, 0xff, 0xff,
+///     0x1c, 0x00, 0x00, 0x00, 0x1c, 0x00, 0x01, 0x00,
+///     0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+/// ];
+/// assert!(parse_messages(&error, 2).is_err());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if a message is truncated, or if
+/// it's an error reply.
+pub fn parse_messages(
+    bytes: &[u8],
+    sequence: u32,
+) -> Result<Replies<'_>, Box<dyn Error>> {
+    let mut payloads = Vec::new();
+    let mut done = false;
+    let mut rest = bytes;
+
+    while rest.len() >= MESSAGE_HEADER_LEN {
+        let len =
+            u32::from_ne_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let kind = u16::from_ne_bytes([rest[4], rest[5]]);
+        let flags = u16::from_ne_bytes([rest[6], rest[7]]);
+        let message_sequence =
+            u32::from_ne_bytes([rest[8], rest[9], rest[10], rest[11]]);
+
+        if len < MESSAGE_HEADER_LEN || len > rest.len() {
+            return Err("Truncated netlink message!".into());
+        }
+
+        let payload = &rest[MESSAGE_HEADER_LEN..len];
+
+        if message_sequence != sequence {
+            // Skipped as a whole, including stale errors and dump ends
+        } else if kind == NLMSG_ERROR {
+            let errno = payload
+                .get(..4)
+                .map(|errno| {
+                    i32::from_ne_bytes([errno[0], errno[1], errno[2], errno[3]])
+                })
+                .ok_or("Truncated netlink error message!")?;
+
+            // An error code of zero acknowledges the request
+            if errno != 0 {
+                return Err(io::Error::from_raw_os_error(-errno).into());
+            }
+            done = true;
+        } else if kind == NLMSG_DONE {
+            done = true;
+        } else {
+            payloads.push(payload);
+            done |= flags & NLM_F_MULTI == 0;
+        }
+
+        rest = &rest[align(len).min(rest.len())..];
+    }
+
+    Ok(Replies { payloads, done })
+}
+
+/// Returns the given attribute encoded and padded, ready to be appended to
+/// a request's attributes.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::netlink::encode_attribute;
+///
+/// assert_eq!(
+///     encode_attribute(52, b"home"),
+///     [8, 0, 52, 0, b'h', b'o', b'm', b'e'],
+/// );
+/// assert_eq!(encode_attribute(2, b"wg\0"), [7, 0, 2, 0, b'w', b'g', 0, 0]);
+/// ```
+pub fn encode_attribute(kind: u16, payload: &[u8]) -> Vec<u8> {
+    let len = ATTRIBUTE_HEADER_LEN + payload.len();
+    let mut attribute = Vec::with_capacity(align(len));
+
+    attribute.extend_from_slice(&(len as u16).to_ne_bytes());
+    attribute.extend_from_slice(&kind.to_ne_bytes());
+    attribute.extend_from_slice(payload);
+    attribute.resize(align(len), 0);
+
+    attribute
+}
+
+/// Returns a generic netlink request for the given ```command``` of the
+/// given ```family```, along with the already encoded ```attributes```.
+pub fn encode_request(
+    family: u16,
+    flags: u16,
+    sequence: u32,
+    command: u8,
+    version: u8,
+    attributes: &[u8],
+) -> Vec<u8> {
+    let len = MESSAGE_HEADER_LEN + GENERIC_HEADER_LEN + attributes.len();
+    let mut request = Vec::with_capacity(len);
+
+    request.extend_from_slice(&(len as u32).to_ne_bytes());
+    request.extend_from_slice(&family.to_ne_bytes());
+    request.extend_from_slice(&flags.to_ne_bytes());
+    request.extend_from_slice(&sequence.to_ne_bytes());
+    // The kernel assigns the port id
+    request.extend_from_slice(&0u32.to_ne_bytes());
+    request.extend_from_slice(&[command, version, 0, 0]);
+    request.extend_from_slice(attributes);
+
+    request
+}
+
+/// Netlink messages and attributes are aligned to 4 bytes.
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
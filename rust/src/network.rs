@@ -0,0 +1,306 @@
+//! The ```network``` module provides a struct containing the download and
+//! upload throughput of the system's network interfaces, sampled
+//! from ```/proc/net/dev```.
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use crate::{
+    net,
+    output::Units,
+    rate::Rate,
+    timesource::{SystemTimeSource, TimeSource},
+    Status,
+};
+
+pub struct Network {
+    pub interfaces: Vec<Interface>,
+    /// The weight given to the newest sample, please refer
+    /// to ```Rate::new```.
+    pub smoothing: f64,
+    /// The units used when displaying human readable rates.
+    pub units: Units,
+    interface_names: Option<Vec<String>>,
+    dev_path: PathBuf,
+    route_path: PathBuf,
+    time: Arc<dyn TimeSource>,
+}
+
+pub struct Interface {
+    pub name: String,
+    pub rx: Rate,
+    pub tx: Rate,
+}
+
+/// The counters of a line of ```/proc/net/dev``` used by ```Network```.
+pub struct InterfaceStats {
+    pub name: String,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+impl Network {
+    /// Where ```interface_names``` are the interfaces to sample, such
+    /// as ```enp0s31f6``` or ```wlp4s0```, or ```None``` to sample whichever
+    /// interface currently has the default route.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use dwmstatus::network::Network;
+    ///
+    /// let network_status = Network::new(Some(&["enp0s31f6", "wlp4s0"]));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/proc/net/dev``` can't
+    /// be read.
+    pub fn new(
+        interface_names: Option<&[&str]>,
+    ) -> Result<Self, Box<dyn Error>> {
+        Network::with_paths(
+            interface_names,
+            "/proc/net/dev",
+            "/proc/net/route",
+            Arc::new(SystemTimeSource),
+        )
+    }
+
+    /// Same as ```Network::new```, except that interfaces are sampled from
+    /// the file at ```dev_path``` instead of ```/proc/net/dev```, that the
+    /// default route is looked up in the file at ```route_path``` instead
+    /// of ```/proc/net/route```, and that samples are timed using the
+    /// given ```time``` source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{fs, process, sync::Arc, time::Duration};
+    /// use chrono::Utc;
+    /// use dwmstatus::{network::Network, timesource::FakeTimeSource, Status};
+    ///
+    /// let dir = std::env::temp_dir()
+    ///     .join(format!("dwmstatus-network-{}", process::id()));
+    /// let (dev, route) = (dir.join("dev"), dir.join("route"));
+    /// let time = Arc::new(FakeTimeSource::new(Utc::now()));
+    /// let header = "Inter-|   Receive    |  Transmit\n \
+    ///               face |bytes    packets|bytes    packets\n";
+    /// let usb0 = |counters: &str| format!("{}usb0: {}\n", header, counters);
+    ///
+    /// fs::create_dir_all(&dir)?;
+    /// fs::write(
+    ///     &route,
+    ///     "Iface\tDestination\tGateway\tFlags\tRefCnt\tUse\tMetric\tMask\n\
+    ///      usb0\t00000000\t012AA8C0\t0003\t0\t0\t100\t00000000\n",
+    /// )?;
+    /// fs::write(&dev, usb0("4096 4 0 0 0 0 0 0 1024 2 0 0 0 0 0 0"))?;
+    ///
+    /// let mut network =
+    ///     Network::with_paths(None, &dev, &route, time.clone())?;
+    /// network.smoothing = 1.0;
+    ///
+    /// fs::write(&dev, usb0("20480 20 0 0 0 0 0 0 3072 6 0 0 0 0 0 0"))?;
+    /// time.advance(Duration::from_secs(2));
+    /// network.update()?;
+    ///
+    /// assert_eq!(network.interfaces[0].name, "usb0");
+    /// assert_eq!(network.rx_bytes_per_second(), 8192.0);
+    /// assert_eq!(network.tx_bytes_per_second(), 1024.0);
+    /// # fs::remove_dir_all(&dir)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the file at ```dev_path```
+    /// can't be read, or if the file at ```route_path``` can't be read when
+    /// no interfaces were given.
+    pub fn with_paths<P: AsRef<Path>, Q: AsRef<Path>>(
+        interface_names: Option<&[&str]>,
+        dev_path: P,
+        route_path: Q,
+        time: Arc<dyn TimeSource>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut network = Network {
+            interfaces: Vec::new(),
+            smoothing: 0.5,
+            units: Units::Iec,
+            interface_names: interface_names.map(|names| {
+                names.iter().map(|name| String::from(*name)).collect()
+            }),
+            dev_path: dev_path.as_ref().to_path_buf(),
+            route_path: route_path.as_ref().to_path_buf(),
+            time,
+        };
+
+        network.update()?;
+
+        Ok(network)
+    }
+
+    pub fn rx_bytes_per_second(&self) -> f64 {
+        self.interfaces
+            .iter()
+            .map(|interface| interface.rx.per_second)
+            .sum()
+    }
+
+    pub fn tx_bytes_per_second(&self) -> f64 {
+        self.interfaces
+            .iter()
+            .map(|interface| interface.tx.per_second)
+            .sum()
+    }
+
+    /// Updates the interfaces' rates using the given ```/proc/net/dev```
+    /// contents sampled at the given instant, along with the name of the
+    /// interface that currently has the default route. Interfaces that
+    /// disappeared are dropped, while new ones are added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{
+    ///     sync::Arc,
+    ///     time::{Duration, Instant},
+    /// };
+    /// use dwmstatus::{network::Network, timesource::SystemTimeSource};
+    ///
+    /// let time = Arc::new(SystemTimeSource);
+    /// let mut network =
+    ///     Network::with_paths(None, "/dev/null", "/dev/null", time)?;
+    /// let start = Instant::now();
+    /// network.smoothing = 1.0;
+    ///
+    /// let contents = |wlan0: &str| {
+    ///     format!(
+    ///         "Inter-|   Receive                            |  Transmit\n \
+    ///          face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed\n    \
+    ///          lo:    1000      10    0    0    0     0          0         0     1000      10    0    0    0     0       0          0\n\
+    ///          wlan0: {}\n",
+    ///         wlan0,
+    ///     )
+    /// };
+    ///
+    /// network.update_from_dev(
+    ///     &contents("4096 4 0 0 0 0 0 0 1024 2 0 0 0 0 0 0"),
+    ///     Some("wlan0"),
+    ///     start,
+    /// );
+    /// network.update_from_dev(
+    ///     &contents("20480 20 0 0 0 0 0 0 3072 6 0 0 0 0 0 0"),
+    ///     Some("wlan0"),
+    ///     start + Duration::from_secs(2),
+    /// );
+    /// assert_eq!(network.rx_bytes_per_second(), 8192.0);
+    /// assert_eq!(network.tx_bytes_per_second(), 1024.0);
+    ///
+    /// // The interface was brought down and up again, resetting its counters
+    /// network.update_from_dev(
+    ///     &contents("512 1 0 0 0 0 0 0 0 0 0 0 0 0 0 0"),
+    ///     Some("wlan0"),
+    ///     start + Duration::from_secs(3),
+    /// );
+    /// assert_eq!(network.rx_bytes_per_second(), 8192.0);
+    ///
+    /// // The default route is gone along with the interface
+    /// let later = start + Duration::from_secs(4);
+    /// network.update_from_dev(&contents(""), None, later);
+    /// assert!(network.interfaces.is_empty());
+    /// assert_eq!(network.rx_bytes_per_second(), 0.0);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn update_from_dev(
+        &mut self,
+        contents: &str,
+        default_interface: Option<&str>,
+        at: Instant,
+    ) {
+        let stats: Vec<InterfaceStats> = parse_net_dev(contents)
+            .into_iter()
+            .filter(|stats| match &self.interface_names {
+                Some(interface_names) => interface_names.contains(&stats.name),
+                None => Some(stats.name.as_str()) == default_interface,
+            })
+            .collect();
+
+        self.interfaces.retain(|interface| {
+            stats.iter().any(|stats| stats.name == interface.name)
+        });
+
+        for stats in stats {
+            let index = match self
+                .interfaces
+                .iter()
+                .position(|interface| interface.name == stats.name)
+            {
+                Some(index) => index,
+                None => {
+                    self.interfaces.push(Interface {
+                        name: stats.name.clone(),
+                        rx: Rate::new(self.smoothing),
+                        tx: Rate::new(self.smoothing),
+                    });
+                    self.interfaces.len() - 1
+                }
+            };
+            let interface = &mut self.interfaces[index];
+
+            interface.rx.set_smoothing(self.smoothing);
+            interface.tx.set_smoothing(self.smoothing);
+
+            interface.rx.sample(stats.rx_bytes, at);
+            interface.tx.sample(stats.tx_bytes, at);
+        }
+    }
+}
+
+impl Status for Network {
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the sampled file can't be
+    /// read, or if the routes can't be read when no interfaces were given.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let contents = fs::read_to_string(&self.dev_path)?;
+        let default_interface = match self.interface_names {
+            Some(_) => None,
+            None => {
+                net::parse_default_route(&fs::read_to_string(&self.route_path)?)
+            }
+        };
+
+        self.update_from_dev(
+            &contents,
+            default_interface.as_deref(),
+            self.time.instant(),
+        );
+
+        Ok(())
+    }
+}
+
+/// Parses the given ```/proc/net/dev``` contents, skipping the headers and
+/// any malformed lines.
+pub fn parse_net_dev(contents: &str) -> Vec<InterfaceStats> {
+    contents
+        .lines()
+        .skip(2)
+        .filter_map(|line| {
+            // Large counters may directly follow the colon
+            let (name, values) = line.split_once(':')?;
+            let values: Vec<&str> = values.split_whitespace().collect();
+
+            Some(InterfaceStats {
+                name: String::from(name.trim()),
+                rx_bytes: values.first()?.parse().ok()?,
+                tx_bytes: values.get(8)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
@@ -1,21 +1,33 @@
 //! The ```output``` module provides default ```fmt::Display``` implementations
 //! for the different ```Status``` structs in the crate as well as
 //! the ```output_statuses``` function for convenience.
+//!
+//! Structs implementing ```Placeholders``` may also be wrapped
+//! in ```Formatted``` in order to display them using a custom format string
+//! instead.
 
-use std::fmt;
+use std::{error::Error, fmt, time::Duration};
 
 use crate::*;
 
 static LIGHT_BLUE_COLOR_FORMAT: &'static str = "^c#68a7d4^";
+static YELLOW_COLOR_FORMAT: &'static str = "^c#d4c368^";
+static RED_COLOR_FORMAT: &'static str = "^c#d46868^";
 static DEFAULT_COLOR_FORMAT: &'static str = "^d^";
 
-/// This function will call ```dwmstatus::set_status_bar`` with the outputs of all
-/// the ```Status``` implementors found in the given ```statuses``` iterator.
+/// WireGuard renews handshakes every two minutes while traffic flows, and
+/// gives up on a handshake after three.
+static WIREGUARD_REKEY_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// This function will call ```dwmstatus::set_status_bar`` with the outputs of
+/// all the ```Status``` implementors found in the given ```statuses```
+/// iterator.
 pub fn output_statuses<'a, T>(statuses: T)
 where
     T: Iterator<Item = &'a Box<dyn Status>>,
 {
-    let status_outputs: Vec<String> = statuses.map(|status| status.to_string()).collect();
+    let status_outputs: Vec<String> =
+        statuses.map(|status| status.to_string()).collect();
 
     let output = format!(
         "{left_padding}{status_output}{right_padding}",
@@ -27,6 +39,203 @@ where
     set_status_bar(&output);
 }
 
+/// The prefixes used when displaying human readable sizes, where ```Iec```
+/// uses powers of 1024 (KiB, MiB, ...) and ```Si``` powers of 1000
+/// (KB, MB, ...).
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Units {
+    Iec,
+    Si,
+}
+
+/// Returns the given number of ```bytes``` as a human readable size using the
+/// largest fitting unit, with a decimal place for values below 10.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::output::{human_bytes, Units};
+///
+/// assert_eq!(human_bytes(512, Units::Iec), "512B");
+/// assert_eq!(human_bytes(1536 * 1024, Units::Iec), "1.5MiB");
+/// assert_eq!(human_bytes(1536 * 1024, Units::Si), "1.6MB");
+/// assert_eq!(human_bytes(20 * 1024 * 1024 * 1024, Units::Iec), "20GiB");
+/// ```
+pub fn human_bytes(bytes: u64, units: Units) -> String {
+    let (base, prefixes) = match units {
+        Units::Iec => (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB"]),
+        Units::Si => (1000.0, ["B", "KB", "MB", "GB", "TB", "PB"]),
+    };
+
+    let mut value = bytes as f64;
+    let mut prefix = 0;
+
+    while value >= base && prefix < prefixes.len() - 1 {
+        value /= base;
+        prefix += 1;
+    }
+
+    if prefix == 0 {
+        format!("{}{}", bytes, prefixes[0])
+    } else if value < 10.0 {
+        format!("{:.1}{}", value, prefixes[prefix])
+    } else {
+        format!("{:.0}{}", value, prefixes[prefix])
+    }
+}
+
+/// Returns the given rate as a human readable size per second, please refer
+/// to ```human_bytes```.
+pub fn human_rate(bytes_per_second: f64, units: Units) -> String {
+    format!("{}/s", human_bytes(bytes_per_second.round() as u64, units))
+}
+
+/// Returns the given duration in its largest fitting unit, rounded down.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use dwmstatus::output::human_duration;
+///
+/// assert_eq!(human_duration(Duration::from_secs(42)), "42s");
+/// assert_eq!(human_duration(Duration::from_secs(150)), "2m");
+/// assert_eq!(human_duration(Duration::from_secs(3 * 3600 + 59)), "3h");
+/// assert_eq!(human_duration(Duration::from_secs(2 * 86400)), "2d");
+/// ```
+pub fn human_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m", seconds / 60),
+        3600..=86399 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+/// The levels at which a status' value should be colored to draw attention.
+#[derive(Clone, Copy)]
+pub struct Thresholds {
+    pub warning: f32,
+    pub critical: f32,
+}
+
+impl Thresholds {
+    /// Returns the color format matching the highest level reached by the
+    /// given ```value```, or an empty string if no level was reached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dwmstatus::output::Thresholds;
+    ///
+    /// let thresholds = Thresholds { warning: 10.0, critical: 30.0 };
+    ///
+    /// assert_eq!(thresholds.color(5.0), "");
+    /// assert_eq!(thresholds.color(42.0), "^c#d46868^");
+    /// ```
+    pub fn color(&self, value: f32) -> &'static str {
+        if value >= self.critical {
+            RED_COLOR_FORMAT
+        } else if value >= self.warning {
+            YELLOW_COLOR_FORMAT
+        } else {
+            ""
+        }
+    }
+}
+
+/// Should be implemented by any ```Status``` struct whose values can be
+/// referenced by name in the format string of a ```Formatted``` status.
+pub trait Placeholders {
+    /// Returns the name and formatted value of each supported placeholder.
+    fn placeholders(&self) -> Vec<(String, String)>;
+}
+
+/// Wraps a ```Status``` in order to display it using the given format
+/// string, where each ```{name}``` is replaced by the value of the matching
+/// placeholder.
+pub struct Formatted<T> {
+    pub status: T,
+    pub format: String,
+}
+
+impl<T> Formatted<T> {
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use dwmstatus::{cpu::Cpu, output::Formatted};
+    ///
+    /// let cpu_status =
+    ///     Formatted::new(Cpu::new()?, "cpu {utilization}% io {iowait}%");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn new(status: T, format: &str) -> Self {
+        Formatted {
+            status,
+            format: String::from(format),
+        }
+    }
+}
+
+impl<T: Status + Placeholders> Status for Formatted<T> {
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        self.status.update()
+    }
+
+    fn act(&mut self, button: u8) -> Result<(), Box<dyn Error>> {
+        self.status.act(button)
+    }
+}
+
+impl<T: Placeholders> fmt::Display for Formatted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", render(&self.format, &self.status.placeholders()))
+    }
+}
+
+/// Replaces each ```{name}``` in ```format``` with the value of the matching
+/// placeholder. Unknown placeholders are left untouched.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::output::render;
+///
+/// let placeholders = vec![(String::from("cpu0"), String::from("12.5"))];
+///
+/// assert_eq!(render("{cpu0}% {cpu1}%", &placeholders), "12.5% {cpu1}%");
+/// ```
+pub fn render(format: &str, placeholders: &[(String, String)]) -> String {
+    let mut output = String::with_capacity(format.len());
+    let mut rest = format;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.find('}') {
+            Some(end) => end,
+            None => break,
+        };
+        let name = &rest[1..end];
+
+        match placeholders.iter().find(|(key, _)| key == name) {
+            Some((_, value)) => output.push_str(value),
+            None => output.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+
+    output
+}
+
+fn placeholder<T: fmt::Display>(name: &str, value: T) -> (String, String) {
+    (String::from(name), value.to_string())
+}
+
 impl fmt::Display for audio::Audio {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -42,7 +251,9 @@ impl fmt::Display for audio::Audio {
 
 impl fmt::Display for battery::Battery {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let percent = self.current_watt_hours as f64 / self.capacity_watt_hours as f64 * 100.0;
+        let percent = self.current_watt_hours as f64
+            / self.capacity_watt_hours as f64
+            * 100.0;
         let icon = if self.charging {
             ''
         } else {
@@ -66,6 +277,104 @@ impl fmt::Display for battery::Battery {
     }
 }
 
+impl fmt::Display for cgroup::Cgroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{0}{icon} {1}{name} {cpu:.1}% {memory}",
+            LIGHT_BLUE_COLOR_FORMAT,
+            DEFAULT_COLOR_FORMAT,
+            icon = '',
+            name = self.name,
+            cpu = self.cpu_utilization,
+            memory = human_bytes(self.memory_bytes, Units::Iec),
+        )
+    }
+}
+
+impl Placeholders for cgroup::Cgroup {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        vec![
+            placeholder("name", &self.name),
+            placeholder("cpu", format!("{:.1}", self.cpu_utilization)),
+            placeholder("memory_h", human_bytes(self.memory_bytes, Units::Iec)),
+            placeholder(
+                "memory_limit_h",
+                self.memory_limit_bytes
+                    .map_or(String::from("max"), |bytes| {
+                        human_bytes(bytes, Units::Iec)
+                    }),
+            ),
+            placeholder(
+                "memory_percent",
+                format!("{:.0}", self.memory_percent),
+            ),
+        ]
+    }
+}
+
+impl fmt::Display for clock::Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format = self.format();
+        let values: Vec<String> = self
+            .shown_zones()
+            .iter()
+            .map(|zone| match zone {
+                Some(zone) => format!(
+                    "{label} {value}",
+                    label = zone.label,
+                    value = self
+                        .timestamp
+                        .with_timezone(&zone.timezone)
+                        .format_localized(&format, self.locale),
+                ),
+                None => self
+                    .timestamp
+                    .format_localized(&format, self.locale)
+                    .to_string(),
+            })
+            .collect();
+
+        write!(
+            f,
+            "{0}{icon} {1}{values}{week}",
+            LIGHT_BLUE_COLOR_FORMAT,
+            DEFAULT_COLOR_FORMAT,
+            icon = if self.shows_time() { '' } else { '' },
+            values = values.join(" | "),
+            week = if self.week {
+                format!(" W{:02}", self.week_number())
+            } else {
+                String::new()
+            },
+        )
+    }
+}
+
+impl Placeholders for clock::Clock {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        let format = self.format();
+        let mut placeholders = vec![
+            placeholder(
+                "value",
+                self.timestamp.format_localized(&format, self.locale),
+            ),
+            placeholder("week", format!("{:02}", self.week_number())),
+        ];
+
+        for zone in &self.zones {
+            placeholders.push(placeholder(
+                &zone.label,
+                self.timestamp
+                    .with_timezone(&zone.timezone)
+                    .format_localized(&format, self.locale),
+            ));
+        }
+
+        placeholders
+    }
+}
+
 impl fmt::Display for cpu::Cpu {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -79,32 +388,339 @@ impl fmt::Display for cpu::Cpu {
     }
 }
 
-impl fmt::Display for date::Date {
+impl Placeholders for cpu::Cpu {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        let breakdown = &self.breakdown;
+        let max_core =
+            self.core_utilization.iter().cloned().fold(0.0, f32::max);
+
+        let mut placeholders = vec![
+            placeholder("utilization", format!("{:.1}", self.utilization)),
+            placeholder("user", format!("{:.1}", breakdown.user)),
+            placeholder("nice", format!("{:.1}", breakdown.nice)),
+            placeholder("system", format!("{:.1}", breakdown.system)),
+            placeholder("iowait", format!("{:.1}", breakdown.iowait)),
+            placeholder("irq", format!("{:.1}", breakdown.irq)),
+            placeholder("steal", format!("{:.1}", breakdown.steal)),
+            placeholder("max_core", format!("{:.1}", max_core)),
+        ];
+        placeholders.extend(self.core_utilization.iter().enumerate().map(
+            |(core, utilization)| {
+                placeholder(
+                    &format!("cpu{}", core),
+                    format!("{:.1}", utilization),
+                )
+            },
+        ));
+
+        placeholders
+    }
+}
+
+impl fmt::Display for cpufreq::CpuFreq {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "{0}{icon} {1}{value}",
+            "{0}{icon} {1}{value:.1}GHz {governor}",
             LIGHT_BLUE_COLOR_FORMAT,
             DEFAULT_COLOR_FORMAT,
-            icon = '',
-            value = self.timestamp.format("%A %x"),
+            icon = '',
+            value = self.average_khz as f64 / 1_000_000.0,
+            governor = self.governor,
         )
     }
 }
 
+impl Placeholders for cpufreq::CpuFreq {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        let ghz = |khz: u64| format!("{:.1}", khz as f64 / 1_000_000.0);
+        let mhz = |khz: u64| khz / 1000;
+
+        vec![
+            placeholder("average_ghz", ghz(self.average_khz)),
+            placeholder("max_ghz", ghz(self.max_khz)),
+            placeholder("average_mhz", mhz(self.average_khz)),
+            placeholder("max_mhz", mhz(self.max_khz)),
+            placeholder("min_limit_mhz", mhz(self.min_limit_khz)),
+            placeholder("max_limit_mhz", mhz(self.max_limit_khz)),
+            placeholder("hardware_max_mhz", mhz(self.hardware_max_khz)),
+            placeholder("governor", &self.governor),
+            placeholder(
+                "epp",
+                self.energy_performance_preference.as_deref().unwrap_or(""),
+            ),
+        ]
+    }
+}
+
 impl fmt::Display for disk::Disk {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let values: Vec<String> = self
+            .mounts
+            .iter()
+            .map(|mount| {
+                format!(
+                    "{label} {value:.0}%",
+                    label = mount.label,
+                    value = mount.used_percent(),
+                )
+            })
+            .collect();
+
         write!(
             f,
-            "{0}{icon} {1}{value:.0}%",
+            "{0}{icon} {1}{values}",
             LIGHT_BLUE_COLOR_FORMAT,
             DEFAULT_COLOR_FORMAT,
             icon = '',
-            value = (1.0 - self.free_bytes as f32 / self.total_bytes as f32) * 100.0,
+            values = values.join(" "),
+        )
+    }
+}
+
+/// The first mount's placeholders are available as is, such as ```{avail_h}```,
+/// while every mount's are also available prefixed by its label, such as
+/// ```{home:avail_h}``` or ```{/:used_percent}```.
+impl Placeholders for disk::Disk {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        let mut placeholders = Vec::new();
+
+        for (index, mount) in self.mounts.iter().enumerate() {
+            let values = [
+                placeholder("label", &mount.label),
+                placeholder("mount_point", &mount.mount_point),
+                placeholder(
+                    "total_h",
+                    human_bytes(mount.total_bytes, self.units),
+                ),
+                placeholder(
+                    "used_h",
+                    human_bytes(mount.used_bytes, self.units),
+                ),
+                placeholder(
+                    "avail_h",
+                    human_bytes(mount.available_bytes, self.units),
+                ),
+                placeholder(
+                    "reserved_h",
+                    human_bytes(mount.reserved_bytes, self.units),
+                ),
+                placeholder(
+                    "used_percent",
+                    format!("{:.0}", mount.used_percent()),
+                ),
+                placeholder("inodes_total", mount.total_inodes),
+                placeholder("inodes_free", mount.free_inodes),
+                placeholder(
+                    "inodes_used_percent",
+                    format!("{:.0}", mount.used_inodes_percent()),
+                ),
+            ];
+
+            if index == 0 {
+                placeholders.extend(values.iter().cloned());
+            }
+            placeholders.extend(values.into_iter().map(|(name, value)| {
+                (format!("{}:{}", mount.label, name), value)
+            }));
+        }
+
+        placeholders
+    }
+}
+
+impl fmt::Display for diskio::DiskIo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{0}{icon} {1}{read} {write}",
+            LIGHT_BLUE_COLOR_FORMAT,
+            DEFAULT_COLOR_FORMAT,
+            icon = '',
+            read = human_rate(self.read_bytes_per_second(), Units::Iec),
+            write = human_rate(self.write_bytes_per_second(), Units::Iec),
+        )
+    }
+}
+
+/// Every device's placeholders are available prefixed by its name, such as
+/// ```{sda:read}```, in addition to the totals.
+impl Placeholders for diskio::DiskIo {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        let mut placeholders = vec![
+            placeholder(
+                "read",
+                human_rate(self.read_bytes_per_second(), Units::Iec),
+            ),
+            placeholder(
+                "write",
+                human_rate(self.write_bytes_per_second(), Units::Iec),
+            ),
+            placeholder("utilization", format!("{:.0}", self.utilization())),
+        ];
+
+        for device in &self.devices {
+            placeholders.extend([
+                placeholder(
+                    &format!("{}:read", device.name),
+                    human_rate(device.read.per_second, Units::Iec),
+                ),
+                placeholder(
+                    &format!("{}:write", device.name),
+                    human_rate(device.write.per_second, Units::Iec),
+                ),
+                placeholder(
+                    &format!("{}:utilization", device.name),
+                    format!("{:.0}", device.utilization()),
+                ),
+            ]);
+        }
+
+        placeholders
+    }
+}
+
+/// Displays the latest round-trip time followed by the recent loss percentage,
+/// if any, or ```down``` when every recent probe was lost.
+impl fmt::Display for latency::Latency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.unreachable() {
+            return write!(
+                f,
+                "{0}{icon} {1}down",
+                RED_COLOR_FORMAT,
+                DEFAULT_COLOR_FORMAT,
+                icon = '',
+            );
+        }
+
+        write!(
+            f,
+            "{0}{icon} {1}{rtt}{loss}",
+            LIGHT_BLUE_COLOR_FORMAT,
+            DEFAULT_COLOR_FORMAT,
+            icon = '',
+            rtt = self.rtt.map_or(String::from("-"), |rtt| format!(
+                "{}ms",
+                rtt.as_millis()
+            )),
+            loss = if self.loss_percent > 0.0 {
+                format!(
+                    " {0}{value:.0}%{1}",
+                    self.thresholds.color(self.loss_percent),
+                    DEFAULT_COLOR_FORMAT,
+                    value = self.loss_percent,
+                )
+            } else {
+                String::new()
+            },
         )
     }
 }
 
+impl Placeholders for latency::Latency {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        vec![
+            placeholder("host", &self.host),
+            placeholder(
+                "rtt",
+                self.rtt
+                    .map_or(String::new(), |rtt| rtt.as_millis().to_string()),
+            ),
+            placeholder("loss", format!("{:.0}", self.loss_percent)),
+        ]
+    }
+}
+
+/// Displays the interface along with its first address when online, such as
+/// ```enp0s31f6 192.168.1.10```, or ```offline``` otherwise.
+impl fmt::Display for link::Link {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.interface_name, self.online()) {
+            (Some(interface_name), true) => write!(
+                f,
+                "{0}{icon} {1}{interface_name}{address}",
+                LIGHT_BLUE_COLOR_FORMAT,
+                DEFAULT_COLOR_FORMAT,
+                icon = if self.wireless { '' } else { '' },
+                interface_name = interface_name,
+                address = self
+                    .ipv4_address()
+                    .or(self.ipv6_address())
+                    .map_or(String::new(), |address| format!(" {}", address)),
+            ),
+            _ => write!(
+                f,
+                "{0}{icon} {1}offline",
+                RED_COLOR_FORMAT,
+                DEFAULT_COLOR_FORMAT,
+                icon = '',
+            ),
+        }
+    }
+}
+
+impl Placeholders for link::Link {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        let address = |address: Option<&std::net::IpAddr>| {
+            address.map_or(String::new(), |address| address.to_string())
+        };
+
+        vec![
+            placeholder(
+                "interface",
+                self.interface_name.clone().unwrap_or_default(),
+            ),
+            placeholder(
+                "state",
+                if self.online() { "online" } else { "offline" },
+            ),
+            placeholder("operstate", &self.operstate),
+            placeholder("carrier", if self.carrier { "yes" } else { "no" }),
+            placeholder(
+                "speed",
+                self.speed_mbps
+                    .map_or(String::new(), |speed| speed.to_string()),
+            ),
+            placeholder("duplex", self.duplex.clone().unwrap_or_default()),
+            placeholder("ipv4", address(self.ipv4_address())),
+            placeholder("ipv6", address(self.ipv6_address())),
+        ]
+    }
+}
+
+impl fmt::Display for load::Load {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (one_minute, five_minutes, fifteen_minutes) = self.averages();
+
+        write!(
+            f,
+            "{0}{icon} {1}{one_minute:.2} {five_minutes:.2} {fifteen_minutes:.2}",
+            LIGHT_BLUE_COLOR_FORMAT,
+            DEFAULT_COLOR_FORMAT,
+            icon = '',
+            one_minute = one_minute,
+            five_minutes = five_minutes,
+            fifteen_minutes = fifteen_minutes,
+        )
+    }
+}
+
+impl Placeholders for load::Load {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        let (one_minute, five_minutes, fifteen_minutes) = self.averages();
+
+        vec![
+            placeholder("load1", format!("{:.2}", one_minute)),
+            placeholder("load5", format!("{:.2}", five_minutes)),
+            placeholder("load15", format!("{:.2}", fifteen_minutes)),
+            placeholder("running", self.running_tasks),
+            placeholder("total", self.total_tasks),
+            placeholder("cpus", self.cpu_count),
+        ]
+    }
+}
+
 impl fmt::Display for memory::Memory {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -113,33 +729,545 @@ impl fmt::Display for memory::Memory {
             LIGHT_BLUE_COLOR_FORMAT,
             DEFAULT_COLOR_FORMAT,
             icon = '',
-            value = (1.0 - self.available_kibibytes as f32 / self.total_kibibytes as f32) * 100.0,
+            value = (1.0
+                - self.available_kibibytes as f32
+                    / self.total_kibibytes as f32)
+                * 100.0,
+        )
+    }
+}
+
+impl Placeholders for memory::Memory {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        let human = |kibibytes: u64| human_bytes(kibibytes * 1024, self.units);
+        let percent = |used: u64, total: u64| {
+            (used * 100).checked_div(total).unwrap_or(0)
+        };
+
+        vec![
+            placeholder(
+                "used_percent",
+                percent(self.used_kibibytes(), self.total_kibibytes),
+            ),
+            placeholder("total_h", human(self.total_kibibytes)),
+            placeholder("used_h", human(self.used_kibibytes())),
+            placeholder("available_h", human(self.available_kibibytes)),
+            placeholder("free_h", human(self.free_kibibytes)),
+            placeholder("buffers_h", human(self.buffers_kibibytes)),
+            placeholder("cached_h", human(self.cached_kibibytes)),
+            placeholder("shmem_h", human(self.shmem_kibibytes)),
+            placeholder("dirty_h", human(self.dirty_kibibytes)),
+            placeholder("writeback_h", human(self.writeback_kibibytes)),
+            placeholder(
+                "swap_percent",
+                percent(self.swap_used_kibibytes(), self.swap_total_kibibytes),
+            ),
+            placeholder("swap_total_h", human(self.swap_total_kibibytes)),
+            placeholder("swap_used_h", human(self.swap_used_kibibytes())),
+            placeholder("swap_free_h", human(self.swap_free_kibibytes)),
+            placeholder("zswap_h", human(self.zswap_kibibytes)),
+            placeholder("zswapped_h", human(self.zswapped_kibibytes)),
+            placeholder(
+                "zram_h",
+                human_bytes(self.zram.used_bytes, self.units),
+            ),
+            placeholder(
+                "zram_original_h",
+                human_bytes(self.zram.original_bytes, self.units),
+            ),
+        ]
+    }
+}
+
+impl fmt::Display for network::Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{0}{icon} {1}{rx} {tx}",
+            LIGHT_BLUE_COLOR_FORMAT,
+            DEFAULT_COLOR_FORMAT,
+            icon = '',
+            rx = human_rate(self.rx_bytes_per_second(), self.units),
+            tx = human_rate(self.tx_bytes_per_second(), self.units),
+        )
+    }
+}
+
+/// Every interface's placeholders are available prefixed by its name, such as
+/// ```{wlp4s0:rx}```, in addition to the totals.
+impl Placeholders for network::Network {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        let mut placeholders = vec![
+            placeholder(
+                "rx",
+                human_rate(self.rx_bytes_per_second(), self.units),
+            ),
+            placeholder(
+                "tx",
+                human_rate(self.tx_bytes_per_second(), self.units),
+            ),
+        ];
+
+        for interface in &self.interfaces {
+            placeholders.extend([
+                placeholder(
+                    &format!("{}:rx", interface.name),
+                    human_rate(interface.rx.per_second, self.units),
+                ),
+                placeholder(
+                    &format!("{}:tx", interface.name),
+                    human_rate(interface.tx.per_second, self.units),
+                ),
+            ]);
+        }
+
+        placeholders
+    }
+}
+
+impl fmt::Display for pressure::Pressure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let stalls =
+            [("cpu", &self.cpu), ("mem", &self.memory), ("io", &self.io)];
+        let values: Vec<String> = stalls
+            .iter()
+            .map(|(label, stall)| {
+                format!(
+                    "{label} {0}{value:.1}{1}",
+                    self.thresholds.color(stall.some.avg10),
+                    DEFAULT_COLOR_FORMAT,
+                    label = label,
+                    value = stall.some.avg10,
+                )
+            })
+            .collect();
+
+        write!(
+            f,
+            "{0}{icon} {1}{values}",
+            LIGHT_BLUE_COLOR_FORMAT,
+            DEFAULT_COLOR_FORMAT,
+            icon = '',
+            values = values.join(" "),
+        )
+    }
+}
+
+impl Placeholders for pressure::Pressure {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        let stalls = [
+            ("cpu", &self.cpu),
+            ("memory", &self.memory),
+            ("io", &self.io),
+        ];
+        let mut placeholders = Vec::new();
+
+        for (name, stall) in stalls {
+            let full = stall.full.unwrap_or_default();
+
+            placeholders.extend([
+                placeholder(
+                    &format!("{}_some10", name),
+                    format!("{:.1}", stall.some.avg10),
+                ),
+                placeholder(
+                    &format!("{}_some60", name),
+                    format!("{:.1}", stall.some.avg60),
+                ),
+                placeholder(
+                    &format!("{}_some300", name),
+                    format!("{:.1}", stall.some.avg300),
+                ),
+                placeholder(
+                    &format!("{}_full10", name),
+                    format!("{:.1}", full.avg10),
+                ),
+                placeholder(
+                    &format!("{}_full60", name),
+                    format!("{:.1}", full.avg60),
+                ),
+                placeholder(
+                    &format!("{}_full300", name),
+                    format!("{:.1}", full.avg300),
+                ),
+            ]);
+        }
+
+        placeholders
+    }
+}
+
+impl fmt::Display for rfkill::Rfkill {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let radios: Vec<String> = self
+            .radios
+            .iter()
+            .map(|radio| match (radio.hard_blocked, radio.soft_blocked) {
+                (true, _) => format!(
+                    "{label} {0}hard{1}",
+                    RED_COLOR_FORMAT,
+                    DEFAULT_COLOR_FORMAT,
+                    label = radio.kind.label(),
+                ),
+                (false, true) => format!(
+                    "{label} {0}off{1}",
+                    YELLOW_COLOR_FORMAT,
+                    DEFAULT_COLOR_FORMAT,
+                    label = radio.kind.label(),
+                ),
+                (false, false) => format!("{} on", radio.kind.label()),
+            })
+            .collect();
+
+        write!(
+            f,
+            "{0}{icon} {1}{radios}",
+            LIGHT_BLUE_COLOR_FORMAT,
+            DEFAULT_COLOR_FORMAT,
+            icon = '',
+            radios = radios.join(" "),
+        )
+    }
+}
+
+impl Placeholders for rfkill::Rfkill {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        let state = |radio: &rfkill::Radio| match (
+            radio.hard_blocked,
+            radio.soft_blocked,
+        ) {
+            (true, _) => "hard",
+            (false, true) => "soft",
+            (false, false) => "on",
+        };
+        let mut placeholders = Vec::new();
+
+        for radio in &self.radios {
+            placeholders.extend([
+                placeholder(
+                    &format!("{}:kind", radio.name),
+                    radio.kind.label(),
+                ),
+                placeholder(&format!("{}:state", radio.name), state(radio)),
+            ]);
+        }
+
+        // The state of the first radio of each kind, such as {wifi} or {bt}
+        for radio in &self.radios {
+            placeholders.push(placeholder(radio.kind.label(), state(radio)));
+        }
+
+        placeholders
+    }
+}
+
+impl fmt::Display for sensors::Sensors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut outputs = Vec::new();
+
+        if !self.temperatures.is_empty() {
+            let temperatures: Vec<String> = self
+                .temperatures
+                .iter()
+                .map(|temperature| {
+                    format!(
+                        "{label} {0}{value:.0}°C{1}",
+                        if temperature.critical() {
+                            RED_COLOR_FORMAT
+                        } else {
+                            ""
+                        },
+                        DEFAULT_COLOR_FORMAT,
+                        label = temperature.label,
+                        value = temperature.celsius,
+                    )
+                })
+                .collect();
+
+            outputs.push(format!(
+                "{0}{icon} {1}{values}",
+                LIGHT_BLUE_COLOR_FORMAT,
+                DEFAULT_COLOR_FORMAT,
+                icon = '',
+                values = temperatures.join(" "),
+            ));
+        }
+
+        if !self.fans.is_empty() {
+            let fans: Vec<String> = self
+                .fans
+                .iter()
+                .map(|fan| format!("{} {}RPM", fan.label, fan.rpm))
+                .collect();
+
+            outputs.push(format!(
+                "{0}{icon} {1}{values}",
+                LIGHT_BLUE_COLOR_FORMAT,
+                DEFAULT_COLOR_FORMAT,
+                icon = '',
+                values = fans.join(" "),
+            ));
+        }
+
+        write!(f, "{}", outputs.join("  "))
+    }
+}
+
+impl Placeholders for sensors::Sensors {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        let temperatures = self.temperatures.iter().flat_map(|temperature| {
+            vec![
+                placeholder(
+                    &temperature.label,
+                    format!("{:.0}", temperature.celsius),
+                ),
+                placeholder(
+                    &format!("{}_crit", temperature.label),
+                    temperature
+                        .critical_celsius
+                        .map_or(String::new(), |celsius| {
+                            format!("{:.0}", celsius)
+                        }),
+                ),
+            ]
+        });
+        let fans = self.fans.iter().map(|fan| placeholder(&fan.label, fan.rpm));
+
+        temperatures.chain(fans).collect()
+    }
+}
+
+/// Displays nothing while storage is healthy, otherwise every problem found in
+/// red, or in yellow if arrays are merely syncing.
+impl fmt::Display for storage::Storage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.healthy() {
+            return Ok(());
+        }
+
+        let mut problems = Vec::new();
+
+        for array in &self.arrays {
+            if array.degraded() {
+                problems.push(if array.active {
+                    format!(
+                        "{} {}/{}",
+                        array.name, array.devices.1, array.devices.0
+                    )
+                } else {
+                    format!("{} inactive", array.name)
+                });
+            }
+
+            if let Some((action, percent, minutes)) = &array.sync {
+                let mut problem = format!("{} {}", array.name, action);
+
+                if let Some(percent) = percent {
+                    problem.push_str(&format!(" {:.1}%", percent));
+                }
+                if let Some(minutes) = minutes {
+                    problem.push_str(&format!(" {:.0}min", minutes));
+                }
+                problems.push(problem);
+            }
+        }
+
+        for device in
+            self.btrfs_devices.iter().filter(|device| device.errors > 0)
+        {
+            problems.push(format!(
+                "{} errors {}",
+                device.filesystem, device.errors
+            ));
+        }
+
+        for pool in self.zfs_pools.iter().filter(|pool| pool.state != "ONLINE")
+        {
+            problems.push(format!(
+                "{} {}",
+                pool.name,
+                pool.state.to_lowercase()
+            ));
+        }
+
+        write!(
+            f,
+            "{0}{icon} {1}{values}",
+            if self.urgent() {
+                RED_COLOR_FORMAT
+            } else {
+                YELLOW_COLOR_FORMAT
+            },
+            DEFAULT_COLOR_FORMAT,
+            icon = '',
+            values = problems.join(" "),
+        )
+    }
+}
+
+impl fmt::Display for top::Top {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{0}{icon} {1}{cpu}{memory_name} {memory}",
+            LIGHT_BLUE_COLOR_FORMAT,
+            DEFAULT_COLOR_FORMAT,
+            icon = '',
+            cpu = if self.cpu_name.is_empty() {
+                String::new()
+            } else {
+                format!("{} {:.0}% ", self.cpu_name, self.cpu_utilization)
+            },
+            memory_name = self.memory_name,
+            memory = human_bytes(self.memory_bytes, Units::Iec),
+        )
+    }
+}
+
+impl Placeholders for top::Top {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        vec![
+            placeholder("cpu_name", &self.cpu_name),
+            placeholder("cpu_pid", self.cpu_pid),
+            placeholder("cpu", format!("{:.1}", self.cpu_utilization)),
+            placeholder("memory_name", &self.memory_name),
+            placeholder("memory_pid", self.memory_pid),
+            placeholder("memory_h", human_bytes(self.memory_bytes, Units::Iec)),
+        ]
+    }
+}
+
+/// Displays the data used today and this month, the latter colored according
+/// to the percentage of the cap used, if any.
+impl fmt::Display for usage::Usage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{0}{icon} {1}{today} {2}{month}{1}",
+            LIGHT_BLUE_COLOR_FORMAT,
+            DEFAULT_COLOR_FORMAT,
+            self.cap_percent()
+                .map_or("", |percent| self.thresholds.color(percent)),
+            icon = '',
+            today = human_bytes(self.today.total(), Units::Iec),
+            month = human_bytes(self.month.total(), Units::Iec),
         )
     }
 }
 
-impl fmt::Display for time::Time {
+impl Placeholders for usage::Usage {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        vec![
+            placeholder("today", human_bytes(self.today.total(), Units::Iec)),
+            placeholder(
+                "today_rx",
+                human_bytes(self.today.rx_bytes, Units::Iec),
+            ),
+            placeholder(
+                "today_tx",
+                human_bytes(self.today.tx_bytes, Units::Iec),
+            ),
+            placeholder("month", human_bytes(self.month.total(), Units::Iec)),
+            placeholder(
+                "month_rx",
+                human_bytes(self.month.rx_bytes, Units::Iec),
+            ),
+            placeholder(
+                "month_tx",
+                human_bytes(self.month.tx_bytes, Units::Iec),
+            ),
+            placeholder(
+                "cap_percent",
+                self.cap_percent()
+                    .map_or(String::new(), |percent| format!("{:.0}", percent)),
+            ),
+        ]
+    }
+}
+
+/// Displays every tunnel's name, in red while down, along with the age of its
+/// last handshake, in yellow once WireGuard should have renewed it.
+impl fmt::Display for vpn::Vpn {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.tunnels.is_empty() {
+            return write!(f, "");
+        }
+
+        let tunnels: Vec<String> = self
+            .tunnels
+            .iter()
+            .map(|tunnel| {
+                let age = tunnel.handshake_age.map_or(String::new(), |age| {
+                    format!(
+                        " {0}{age}{1}",
+                        if age > WIREGUARD_REKEY_TIMEOUT {
+                            YELLOW_COLOR_FORMAT
+                        } else {
+                            ""
+                        },
+                        DEFAULT_COLOR_FORMAT,
+                        age = human_duration(age),
+                    )
+                });
+
+                format!(
+                    "{0}{name}{1}{age}",
+                    if tunnel.up { "" } else { RED_COLOR_FORMAT },
+                    DEFAULT_COLOR_FORMAT,
+                    name = tunnel.name,
+                    age = age,
+                )
+            })
+            .collect();
+
         write!(
             f,
-            "{0}{icon} {1}{value}",
+            "{0}{icon} {1}{values}",
             LIGHT_BLUE_COLOR_FORMAT,
             DEFAULT_COLOR_FORMAT,
-            icon = '',
-            value = self.timestamp.format("%I:%M %p"),
+            icon = '',
+            values = tunnels.join(" "),
         )
     }
 }
 
+/// Every tunnel's placeholders are available prefixed by its name, such as
+/// ```{wg0:state}```.
+impl Placeholders for vpn::Vpn {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        self.tunnels
+            .iter()
+            .flat_map(|tunnel| {
+                vec![
+                    placeholder(
+                        &format!("{}:state", tunnel.name),
+                        if tunnel.up { "up" } else { "down" },
+                    ),
+                    placeholder(
+                        &format!("{}:handshake", tunnel.name),
+                        tunnel
+                            .handshake_age
+                            .map_or(String::new(), human_duration),
+                    ),
+                ]
+            })
+            .collect()
+    }
+}
+
 impl fmt::Display for wifi::Wifi {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.active {
             write!(
                 f,
-                "{0}{icon} {1}{value:.1}%",
+                "{0}{icon} {1}{ssid}{value:.0}%",
                 LIGHT_BLUE_COLOR_FORMAT,
                 DEFAULT_COLOR_FORMAT,
                 icon = '',
+                ssid = self
+                    .ssid
+                    .as_ref()
+                    .map_or(String::new(), |ssid| format!("{} ", ssid)),
                 value = self.strength,
             )
         } else {
@@ -147,3 +1275,41 @@ impl fmt::Display for wifi::Wifi {
         }
     }
 }
+
+impl Placeholders for wifi::Wifi {
+    fn placeholders(&self) -> Vec<(String, String)> {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+
+        vec![
+            placeholder("interface", optional(self.interface_name.clone())),
+            placeholder("ssid", optional(self.ssid.clone())),
+            placeholder("bssid", optional(self.bssid.clone())),
+            placeholder("quality", format!("{:.0}", self.strength)),
+            placeholder(
+                "signal",
+                optional(self.signal_dbm.map(|dbm| dbm.to_string())),
+            ),
+            placeholder(
+                "frequency",
+                optional(self.frequency_mhz.map(|mhz| mhz.to_string())),
+            ),
+            placeholder("band", self.band().unwrap_or_default()),
+            placeholder(
+                "channel",
+                optional(self.channel().map(|channel| channel.to_string())),
+            ),
+            placeholder(
+                "tx_bitrate",
+                optional(
+                    self.tx_bitrate_mbps.map(|mbps| format!("{:.0}", mbps)),
+                ),
+            ),
+            placeholder(
+                "rx_bitrate",
+                optional(
+                    self.rx_bitrate_mbps.map(|mbps| format!("{:.0}", mbps)),
+                ),
+            ),
+        ]
+    }
+}
@@ -0,0 +1,190 @@
+//! The ```pressure``` module provides a struct containing the Pressure Stall
+//! Information (PSI) of the system or a cgroup, which is the share of time
+//! tasks were stalled waiting on the cpu, memory or io.
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{output::Thresholds, Status};
+
+pub struct Pressure {
+    pub cpu: Stall,
+    pub memory: Stall,
+    pub io: Stall,
+    /// Used to color the ```some``` 10 second averages when displayed.
+    pub thresholds: Thresholds,
+    paths: [PathBuf; 3],
+}
+
+/// The contents of a single pressure file.
+///
+/// ```some``` covers time where at least one task was stalled, while ```full```
+/// covers time where all non-idle tasks were stalled at once. The latter is
+/// missing for the system-wide cpu pressure on kernels prior to 5.13.
+#[derive(Clone, Copy, Default)]
+pub struct Stall {
+    pub some: Averages,
+    pub full: Option<Averages>,
+}
+
+/// Percentages of time stalled over the last 10, 60 and 300 seconds, along
+/// with the total time stalled in microseconds.
+#[derive(Clone, Copy, Default)]
+pub struct Averages {
+    pub avg10: f32,
+    pub avg60: f32,
+    pub avg300: f32,
+    pub total_usecs: u64,
+}
+
+impl Stall {
+    /// # Examples
+    ///
+    /// ```
+    /// use dwmstatus::pressure::Stall;
+    ///
+    /// let stall = Stall::parse(
+    ///     "some avg10=5.46 avg60=3.91 avg300=2.01 total=11463355\n\
+    ///      full avg10=1.20 avg60=0.50 avg300=0.10 total=4233\n",
+    /// )?;
+    ///
+    /// assert_eq!(stall.some.avg10, 5.46);
+    /// assert_eq!(stall.some.total_usecs, 11463355);
+    /// assert_eq!(stall.full.unwrap().avg60, 0.5);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```contents``` doesn't
+    /// contain a valid ```some``` line.
+    pub fn parse(contents: &str) -> Result<Self, Box<dyn Error>> {
+        let mut some = None;
+        let mut full = None;
+
+        for line in contents.lines() {
+            let mut values = line.split_whitespace();
+
+            match values.next() {
+                Some("some") => some = Some(Averages::parse(values)?),
+                Some("full") => full = Some(Averages::parse(values)?),
+                _ => continue,
+            }
+        }
+
+        Ok(Stall {
+            some: some.ok_or("No 'some' line found in pressure file!")?,
+            full,
+        })
+    }
+}
+
+impl Averages {
+    fn parse<'a, T: Iterator<Item = &'a str>>(
+        values: T,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut averages = Averages::default();
+
+        for value in values {
+            match value.split_once('=') {
+                Some(("avg10", value)) => averages.avg10 = value.parse()?,
+                Some(("avg60", value)) => averages.avg60 = value.parse()?,
+                Some(("avg300", value)) => averages.avg300 = value.parse()?,
+                Some(("total", value)) => {
+                    averages.total_usecs = value.parse()?
+                }
+                _ => continue,
+            }
+        }
+
+        Ok(averages)
+    }
+}
+
+impl Pressure {
+    /// Reports the system-wide pressure found in ```/proc/pressure```.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the pressure files can't be
+    /// read, which is the case when the kernel was built or booted without
+    /// PSI support.
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let dir = Path::new("/proc/pressure");
+
+        Pressure::with_paths([
+            dir.join("cpu"),
+            dir.join("memory"),
+            dir.join("io"),
+        ])
+    }
+
+    /// Reports the pressure of the cgroup v2 found at ```path```, which is
+    /// relative to ```/sys/fs/cgroup``` unless absolute.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use dwmstatus::pressure::Pressure;
+    ///
+    /// let pressure_status =
+    ///     Pressure::for_cgroup("user.slice/user-1000.slice");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the cgroup's pressure files
+    /// can't be read.
+    pub fn for_cgroup<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let dir = Path::new("/sys/fs/cgroup").join(path);
+
+        Pressure::with_paths([
+            dir.join("cpu.pressure"),
+            dir.join("memory.pressure"),
+            dir.join("io.pressure"),
+        ])
+    }
+
+    fn with_paths(paths: [PathBuf; 3]) -> Result<Self, Box<dyn Error>> {
+        let mut pressure = Pressure {
+            cpu: Stall::default(),
+            memory: Stall::default(),
+            io: Stall::default(),
+            thresholds: Thresholds {
+                warning: 10.0,
+                critical: 30.0,
+            },
+            paths,
+        };
+
+        pressure.update()?;
+
+        Ok(pressure)
+    }
+}
+
+impl Status for Pressure {
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if any of the pressure files
+    /// can't be opened for reading or parsed.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let [cpu, memory, io] = &self.paths;
+
+        self.cpu = read_stall(cpu)?;
+        self.memory = read_stall(memory)?;
+        self.io = read_stall(io)?;
+
+        Ok(())
+    }
+}
+
+fn read_stall(path: &Path) -> Result<Stall, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read {:?}: {}", path, err))?;
+
+    Stall::parse(&contents)
+}
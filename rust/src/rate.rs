@@ -0,0 +1,88 @@
+//! The ```rate``` module provides a helper struct for turning ever increasing
+//! counters, such as the number of bytes read from a disk, into smoothed per
+//! second rates.
+
+use std::time::Instant;
+
+pub struct Rate {
+    pub per_second: f64,
+    smoothing: f64,
+    previous: Option<(u64, Instant)>,
+    has_rate: bool,
+}
+
+impl Rate {
+    /// Where ```smoothing``` is the weight given to the newest sample, where
+    /// ```1.0``` disables smoothing altogether and values closer to ```0.0```
+    /// smooth more.
+    pub fn new(smoothing: f64) -> Self {
+        Rate {
+            per_second: 0.0,
+            smoothing: smoothing.clamp(0.0, 1.0),
+            previous: None,
+            has_rate: false,
+        }
+    }
+
+    pub fn smoothing(&self) -> f64 {
+        self.smoothing
+    }
+
+    /// Sets the weight given to the newest sample, clamped between ```0.0```
+    /// and ```1.0```.
+    pub fn set_smoothing(&mut self, smoothing: f64) {
+        self.smoothing = smoothing.clamp(0.0, 1.0);
+    }
+
+    /// Updates ```per_second``` using the counter's ```value``` sampled at the
+    /// given instant.
+    ///
+    /// The first sample, as well as samples following a counter reset, only
+    /// serve as the basis for the next one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use dwmstatus::rate::Rate;
+    ///
+    /// let start = Instant::now();
+    /// let mut rate = Rate::new(0.5);
+    ///
+    /// rate.sample(1000, start);
+    /// rate.sample(3000, start + Duration::from_secs(1));
+    /// assert_eq!(rate.per_second, 2000.0);
+    ///
+    /// rate.sample(3000, start + Duration::from_secs(2));
+    /// assert_eq!(rate.per_second, 1000.0);
+    ///
+    /// // The counter was reset, such as when a device was re-plugged
+    /// rate.sample(10, start + Duration::from_secs(3));
+    /// assert_eq!(rate.per_second, 1000.0);
+    /// ```
+    pub fn sample(&mut self, value: u64, at: Instant) {
+        if let Some((previous_value, previous_at)) = self.previous {
+            let elapsed =
+                at.saturating_duration_since(previous_at).as_secs_f64();
+
+            if elapsed == 0.0 {
+                return;
+            }
+
+            if value >= previous_value {
+                let per_second = (value - previous_value) as f64 / elapsed;
+
+                // The first rate has no previous one to be smoothed with
+                self.per_second = if self.has_rate {
+                    self.smoothing * per_second
+                        + (1.0 - self.smoothing) * self.per_second
+                } else {
+                    per_second
+                };
+                self.has_rate = true;
+            }
+        }
+
+        self.previous = Some((value, at));
+    }
+}
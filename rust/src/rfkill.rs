@@ -0,0 +1,376 @@
+//! The ```rfkill``` module provides a struct containing whether the system's
+//! radios, such as wifi and bluetooth ones, are soft blocked by software or
+//! hard blocked by a hardware switch.
+//!
+//! Events are read from ```/dev/rfkill``` by a background thread, which
+//! requests a refresh as soon as a radio's state changes. When it can't be
+//! opened, ```/sys/class/rfkill``` is read on every update instead.
+
+use std::{
+    error::Error,
+    fs::{self, File, OpenOptions},
+    io::{Read, Write},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crate::{request_refresh, Status};
+
+static RFKILL_OP_ADD: u8 = 0;
+static RFKILL_OP_DEL: u8 = 1;
+static RFKILL_OP_CHANGE: u8 = 2;
+static RFKILL_OP_CHANGE_ALL: u8 = 3;
+
+/// The size of the original ```rfkill_event```, which newer kernels still
+/// return when asked for no more.
+static EVENT_LEN: usize = 8;
+
+pub struct Rfkill {
+    pub radios: Vec<Radio>,
+    /// The kind of radio toggled by a left click, while a right click toggles
+    /// every radio.
+    pub toggled_kind: RadioKind,
+    events: Option<Arc<Mutex<Vec<Radio>>>>,
+}
+
+#[derive(Clone)]
+pub struct Radio {
+    pub index: u32,
+    pub kind: RadioKind,
+    /// The name of the radio's device, such as ```phy0``` or ```hci0```.
+    pub name: String,
+    pub soft_blocked: bool,
+    pub hard_blocked: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RadioKind {
+    All,
+    Wlan,
+    Bluetooth,
+    Uwb,
+    Wimax,
+    Wwan,
+    Gps,
+    Fm,
+    Nfc,
+    Other(u8),
+}
+
+/// An event read from ```/dev/rfkill```.
+pub struct Event {
+    pub index: u32,
+    pub kind: RadioKind,
+    /// Whether the radio was added, deleted or changed.
+    pub operation: u8,
+    pub soft_blocked: bool,
+    pub hard_blocked: bool,
+}
+
+impl RadioKind {
+    pub fn from_u8(kind: u8) -> Self {
+        match kind {
+            0 => RadioKind::All,
+            1 => RadioKind::Wlan,
+            2 => RadioKind::Bluetooth,
+            3 => RadioKind::Uwb,
+            4 => RadioKind::Wimax,
+            5 => RadioKind::Wwan,
+            6 => RadioKind::Gps,
+            7 => RadioKind::Fm,
+            8 => RadioKind::Nfc,
+            kind => RadioKind::Other(kind),
+        }
+    }
+
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            RadioKind::All => 0,
+            RadioKind::Wlan => 1,
+            RadioKind::Bluetooth => 2,
+            RadioKind::Uwb => 3,
+            RadioKind::Wimax => 4,
+            RadioKind::Wwan => 5,
+            RadioKind::Gps => 6,
+            RadioKind::Fm => 7,
+            RadioKind::Nfc => 8,
+            RadioKind::Other(kind) => *kind,
+        }
+    }
+
+    /// Parses the names used by ```/sys/class/rfkill/*/type```.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "wlan" => RadioKind::Wlan,
+            "bluetooth" => RadioKind::Bluetooth,
+            "ultrawideband" => RadioKind::Uwb,
+            "wimax" => RadioKind::Wimax,
+            "wwan" => RadioKind::Wwan,
+            "gps" => RadioKind::Gps,
+            "fm" => RadioKind::Fm,
+            "nfc" => RadioKind::Nfc,
+            _ => RadioKind::Other(u8::MAX),
+        }
+    }
+
+    /// A short name suitable for the status bar.
+    pub fn label(&self) -> &'static str {
+        match self {
+            RadioKind::All => "all",
+            RadioKind::Wlan => "wifi",
+            RadioKind::Bluetooth => "bt",
+            RadioKind::Uwb => "uwb",
+            RadioKind::Wimax => "wimax",
+            RadioKind::Wwan => "wwan",
+            RadioKind::Gps => "gps",
+            RadioKind::Fm => "fm",
+            RadioKind::Nfc => "nfc",
+            RadioKind::Other(_) => "other",
+        }
+    }
+}
+
+impl Event {
+    /// Parses an event read from ```/dev/rfkill```, ignoring any fields
+    /// following the original ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dwmstatus::rfkill::{Event, RadioKind};
+    ///
+    /// let event = Event::parse(&[1, 0, 0, 0, 2, 2, 1, 0]).unwrap();
+    ///
+    /// assert_eq!(event.index, 1);
+    /// assert_eq!(event.kind, RadioKind::Bluetooth);
+    /// assert!(event.soft_blocked);
+    /// assert!(!event.hard_blocked);
+    /// ```
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let bytes = bytes.get(..EVENT_LEN)?;
+
+        Some(Event {
+            index: u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            kind: RadioKind::from_u8(bytes[4]),
+            operation: bytes[5],
+            soft_blocked: bytes[6] != 0,
+            hard_blocked: bytes[7] != 0,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let index = self.index.to_ne_bytes();
+
+        [
+            index[0],
+            index[1],
+            index[2],
+            index[3],
+            self.kind.as_u8(),
+            self.operation,
+            self.soft_blocked as u8,
+            self.hard_blocked as u8,
+        ]
+    }
+}
+
+impl Rfkill {
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use dwmstatus::rfkill::Rfkill;
+    ///
+    /// let rfkill_status = Rfkill::new();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if neither ```/dev/rfkill```
+    /// nor ```/sys/class/rfkill``` can be read.
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        let mut rfkill = Rfkill {
+            radios: Vec::new(),
+            toggled_kind: RadioKind::Wlan,
+            events: None,
+        };
+
+        if let Ok(file) = File::open("/dev/rfkill") {
+            let radios = Arc::new(Mutex::new(Vec::new()));
+            let thread_radios = radios.clone();
+
+            thread::Builder::new()
+                .name(String::from("rfkill"))
+                .spawn(move || read_events(file, thread_radios))?;
+
+            rfkill.events = Some(radios);
+        }
+
+        rfkill.update()?;
+
+        Ok(rfkill)
+    }
+
+    /// Unblocks every radio of the given kind if any of them is soft blocked,
+    /// and blocks them otherwise. Hard blocked radios can only be unblocked
+    /// using their hardware switch.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/dev/rfkill``` can't be
+    /// written, which requires either root or an active session's access.
+    pub fn toggle(&mut self, kind: RadioKind) -> Result<(), Box<dyn Error>> {
+        let blocked = self
+            .radios
+            .iter()
+            .filter(|radio| kind == RadioKind::All || radio.kind == kind)
+            .any(|radio| radio.soft_blocked);
+        let event = Event {
+            index: 0,
+            kind,
+            operation: RFKILL_OP_CHANGE_ALL,
+            soft_blocked: !blocked,
+            hard_blocked: false,
+        };
+
+        OpenOptions::new()
+            .write(true)
+            .open("/dev/rfkill")?
+            .write_all(&event.to_bytes())?;
+
+        Ok(())
+    }
+}
+
+impl Status for Rfkill {
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/dev/rfkill``` couldn't
+    /// be opened and ```/sys/class/rfkill``` can't be read.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        self.radios = match &self.events {
+            Some(radios) => radios
+                .lock()
+                .map_err(|_| "Rfkill event thread panicked!")?
+                .clone(),
+            None => read_sysfs_radios()?,
+        };
+
+        Ok(())
+    }
+
+    /// Toggles ```toggled_kind``` radios on left click, and every radio on
+    /// right click.
+    fn act(&mut self, button: u8) -> Result<(), Box<dyn Error>> {
+        match button {
+            1 => self.toggle(self.toggled_kind),
+            3 => self.toggle(RadioKind::All),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Applies the given event to ```radios```, adding, removing or changing the
+/// radio in question.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::rfkill::{apply_event, Event, Radio};
+///
+/// let mut radios: Vec<Radio> = Vec::new();
+///
+/// apply_event(&mut radios, &Event::parse(&[0, 0, 0, 0, 1, 0, 0, 0]).unwrap());
+/// apply_event(&mut radios, &Event::parse(&[1, 0, 0, 0, 2, 0, 0, 0]).unwrap());
+/// apply_event(&mut radios, &Event::parse(&[0, 0, 0, 0, 1, 2, 0, 1]).unwrap());
+/// assert_eq!(radios.len(), 2);
+/// assert!(radios[0].hard_blocked);
+///
+/// apply_event(&mut radios, &Event::parse(&[1, 0, 0, 0, 2, 1, 0, 0]).unwrap());
+/// assert_eq!(radios.len(), 1);
+/// ```
+pub fn apply_event(radios: &mut Vec<Radio>, event: &Event) {
+    let position = radios.iter().position(|radio| radio.index == event.index);
+
+    match (event.operation, position) {
+        (operation, Some(index)) if operation == RFKILL_OP_DEL => {
+            radios.remove(index);
+        }
+        (operation, Some(index)) if operation != RFKILL_OP_CHANGE_ALL => {
+            radios[index].soft_blocked = event.soft_blocked;
+            radios[index].hard_blocked = event.hard_blocked;
+        }
+        (operation, None)
+            if operation == RFKILL_OP_ADD || operation == RFKILL_OP_CHANGE =>
+        {
+            radios.push(Radio {
+                index: event.index,
+                kind: event.kind,
+                name: read_name(event.index),
+                soft_blocked: event.soft_blocked,
+                hard_blocked: event.hard_blocked,
+            });
+            radios.sort_by_key(|radio| radio.index);
+        }
+        _ => {}
+    }
+}
+
+/// Reads events until ```/dev/rfkill``` can't be read anymore, starting with
+/// an event adding each existing radio.
+fn read_events(mut file: File, radios: Arc<Mutex<Vec<Radio>>>) {
+    let mut buffer = [0u8; EVENT_LEN];
+
+    while let Ok(len) = file.read(&mut buffer) {
+        let event = match Event::parse(&buffer[..len]) {
+            Some(event) => event,
+            None => break,
+        };
+
+        match radios.lock() {
+            Ok(mut radios) => apply_event(&mut radios, &event),
+            Err(_) => break,
+        }
+
+        request_refresh();
+    }
+}
+
+fn read_name(index: u32) -> String {
+    fs::read_to_string(format!("/sys/class/rfkill/rfkill{}/name", index))
+        .map(|name| name.trim().to_string())
+        .unwrap_or_default()
+}
+
+fn read_sysfs_radios() -> Result<Vec<Radio>, Box<dyn Error>> {
+    let mut radios = Vec::new();
+
+    for entry in fs::read_dir("/sys/class/rfkill")? {
+        let dir = entry?.path();
+        let read = |name: &str| {
+            fs::read_to_string(dir.join(name))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+        let index = match dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix("rfkill"))
+            .and_then(|index| index.parse().ok())
+        {
+            Some(index) => index,
+            None => continue,
+        };
+
+        radios.push(Radio {
+            index,
+            kind: RadioKind::from_name(&read("type")),
+            name: read("name"),
+            soft_blocked: read("soft") == "1",
+            hard_blocked: read("hard") == "1",
+        });
+    }
+
+    radios.sort_by_key(|radio| radio.index);
+
+    Ok(radios)
+}
@@ -0,0 +1,428 @@
+//! The ```sensors``` module provides a struct containing the readings of the
+//! system's temperature and fan sensors, as exposed by ```hwmon``` chips and
+//! thermal zones.
+//!
+//! Sensors are selected by their chip name and label rather than by their
+//! ```hwmonN``` directory, since that numbering may change across reboots.
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{sysfs::read_string, Status};
+
+/// The selectors of the sensors reporting the cpu package's temperature with
+/// common drivers, from the most to the least preferred.
+static CPU_SELECTORS: &[&str] = &[
+    "coretemp/Package id 0",
+    "k10temp/Tctl",
+    "zenpower/Tdie",
+    "thermal/x86_pkg_temp",
+    "cpu_thermal/*",
+];
+
+pub struct Sensors {
+    pub temperatures: Vec<Temperature>,
+    pub fans: Vec<Fan>,
+    groups: Vec<(String, Vec<Sensor>)>,
+}
+
+/// The highest reading amongst the temperature sensors of a group, along
+/// with the lowest critical threshold amongst them.
+pub struct Temperature {
+    pub label: String,
+    pub celsius: f32,
+    pub critical_celsius: Option<f32>,
+}
+
+/// The highest reading amongst the fan sensors of a group.
+pub struct Fan {
+    pub label: String,
+    pub rpm: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SensorKind {
+    Temperature,
+    Fan,
+}
+
+/// A sensor discovered in either ```/sys/class/hwmon``` or
+/// ```/sys/class/thermal```.
+///
+/// Thermal zones use ```thermal``` as their chip name and their type, such
+/// as ```x86_pkg_temp```, as their label.
+#[derive(Clone)]
+pub struct Sensor {
+    pub chip: String,
+    pub label: String,
+    pub kind: SensorKind,
+    pub critical_celsius: Option<f32>,
+    input: PathBuf,
+}
+
+impl Temperature {
+    pub fn critical(&self) -> bool {
+        self.critical_celsius
+            .is_some_and(|critical_celsius| self.celsius >= critical_celsius)
+    }
+}
+
+impl Sensor {
+    /// Returns whether the sensor is matched by the given ```selector```,
+    /// written as ```chip/label```, where a label of ```*``` matches every
+    /// sensor of the chip.
+    pub fn matches(&self, selector: &str) -> bool {
+        match selector.split_once('/') {
+            Some((chip, label)) => {
+                chip == self.chip && (label == "*" || label == self.label)
+            }
+            None => false,
+        }
+    }
+
+    fn read(&self) -> Result<f32, Box<dyn Error>> {
+        let value: f32 = read_string(&self.input)?.parse()?;
+
+        Ok(match self.kind {
+            SensorKind::Temperature => value / 1000.0,
+            SensorKind::Fan => value,
+        })
+    }
+}
+
+impl Sensors {
+    /// Where ```groups``` are the labels to display along with the selectors
+    /// of the sensors whose highest reading should be displayed for them.
+    /// Please refer to ```Sensor::matches``` for the selector format.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use dwmstatus::sensors::Sensors;
+    ///
+    /// let sensors_status = Sensors::new(&[
+    ///     ("cpu", &["coretemp/Package id 0"]),
+    ///     ("nvme", &["nvme/*"]),
+    ///     ("fan", &["thinkpad/fan1"]),
+    /// ]);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the sensors can't be
+    /// discovered or one of the ```groups``` doesn't match any sensor.
+    pub fn new(groups: &[(&str, &[&str])]) -> Result<Self, Box<dyn Error>> {
+        Sensors::with_root("/sys/class", groups)
+    }
+
+    /// Displays the cpu package's temperature as ```cpu```, using the first
+    /// sensor found amongst those of common drivers, such as ```coretemp```
+    /// on Intel and ```k10temp``` on AMD.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the sensors can't be
+    /// discovered or none of them reports the cpu package's temperature, as
+    /// is usually the case in virtual machines.
+    pub fn cpu() -> Result<Self, Box<dyn Error>> {
+        let sensors = discover(Path::new("/sys/class"))?;
+        let selector =
+            cpu_selector(&sensors).ok_or("No cpu temperature sensor found!")?;
+
+        Sensors::new(&[("cpu", &[selector])])
+    }
+
+    /// Same as ```Sensors::new```, except that sensors are discovered
+    /// within ```root``` instead of ```/sys/class```.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{fs, process};
+    /// use dwmstatus::{sensors::Sensors, Status};
+    ///
+    /// let root = std::env::temp_dir()
+    ///     .join(format!("dwmstatus-sensors-{}", process::id()));
+    /// let chip = root.join("hwmon/hwmon3");
+    /// let zone = root.join("thermal/thermal_zone0");
+    /// fs::create_dir_all(&chip)?;
+    /// fs::create_dir_all(&zone)?;
+    ///
+    /// fs::write(chip.join("name"), "coretemp\n")?;
+    /// fs::write(chip.join("temp1_label"), "Core 0\n")?;
+    /// fs::write(chip.join("temp1_input"), "54000\n")?;
+    /// fs::write(chip.join("temp1_crit"), "100000\n")?;
+    /// fs::write(chip.join("temp2_label"), "Core 1\n")?;
+    /// fs::write(chip.join("temp2_input"), "61500\n")?;
+    /// fs::write(chip.join("fan1_input"), "2400\n")?;
+    /// fs::write(zone.join("type"), "acpitz\n")?;
+    /// fs::write(zone.join("temp"), "45000\n")?;
+    /// fs::write(zone.join("trip_point_0_type"), "critical\n")?;
+    /// fs::write(zone.join("trip_point_0_temp"), "98000\n")?;
+    ///
+    /// let mut sensors = Sensors::with_root(
+    ///     &root,
+    ///     &[
+    ///         ("cpu", &["coretemp/*"]),
+    ///         ("acpi", &["thermal/acpitz"]),
+    ///         ("fan", &["coretemp/fan1"]),
+    ///     ],
+    /// )?;
+    /// sensors.update()?;
+    ///
+    /// assert_eq!(sensors.temperatures[0].celsius, 61.5);
+    /// assert_eq!(sensors.temperatures[0].critical_celsius, Some(100.0));
+    /// assert_eq!(sensors.temperatures[1].critical_celsius, Some(98.0));
+    /// assert_eq!(sensors.fans[0].rpm, 2400);
+    ///
+    /// fs::write(chip.join("temp2_input"), "100500\n")?;
+    /// sensors.update()?;
+    /// assert!(sensors.temperatures[0].critical());
+    ///
+    /// // Sensors that can't be read are skipped
+    /// fs::write(chip.join("temp2_input"), "")?;
+    /// sensors.update()?;
+    /// assert_eq!(sensors.temperatures[0].celsius, 54.0);
+    /// assert_eq!(sensors.temperatures.len(), 2);
+    /// # fs::remove_dir_all(&root)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_root<P: AsRef<Path>>(
+        root: P,
+        groups: &[(&str, &[&str])],
+    ) -> Result<Self, Box<dyn Error>> {
+        let sensors = discover(root.as_ref())?;
+        let mut selected_groups = Vec::new();
+
+        for (label, selectors) in groups {
+            let matching: Vec<Sensor> = sensors
+                .iter()
+                .filter(|sensor| {
+                    selectors.iter().any(|selector| sensor.matches(selector))
+                })
+                .cloned()
+                .collect();
+
+            if matching.is_empty() {
+                return Err(
+                    format!("No sensors found for {:?}!", selectors).into()
+                );
+            }
+
+            selected_groups.push((String::from(*label), matching));
+        }
+
+        Ok(Sensors {
+            temperatures: Vec::new(),
+            fans: Vec::new(),
+            groups: selected_groups,
+        })
+    }
+}
+
+impl Status for Sensors {
+    /// Sensors that can't be read, such as when their chip's driver was
+    /// unloaded, are skipped, so that a group is only left out when none of
+    /// its sensors can be read.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        self.temperatures.clear();
+        self.fans.clear();
+
+        for (label, sensors) in &self.groups {
+            let mut temperature: Option<Temperature> = None;
+            let mut fan: Option<Fan> = None;
+
+            let critical_celsius = sensors
+                .iter()
+                .filter_map(|sensor| sensor.critical_celsius)
+                .reduce(f32::min);
+
+            for sensor in sensors {
+                let value = match sensor.read() {
+                    Ok(value) => value,
+                    Err(_) => continue,
+                };
+
+                match sensor.kind {
+                    SensorKind::Temperature => {
+                        if temperature
+                            .as_ref()
+                            .is_none_or(|max| value > max.celsius)
+                        {
+                            temperature = Some(Temperature {
+                                label: label.clone(),
+                                celsius: value,
+                                critical_celsius,
+                            });
+                        }
+                    }
+                    SensorKind::Fan => {
+                        if fan.as_ref().is_none_or(|max| value as u32 > max.rpm)
+                        {
+                            fan = Some(Fan {
+                                label: label.clone(),
+                                rpm: value as u32,
+                            });
+                        }
+                    }
+                }
+            }
+
+            self.temperatures.extend(temperature);
+            self.fans.extend(fan);
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the selector of the preferred sensor reporting the cpu package's
+/// temperature amongst the given ```sensors```, if any.
+///
+/// # Examples
+///
+/// ```
+/// use std::{fs, process};
+/// use dwmstatus::sensors::{cpu_selector, discover};
+///
+/// let root = std::env::temp_dir()
+///     .join(format!("dwmstatus-cpu-selector-{}", process::id()));
+/// let chip = root.join("hwmon/hwmon1");
+/// fs::create_dir_all(&chip)?;
+/// fs::write(chip.join("name"), "k10temp\n")?;
+/// fs::write(chip.join("temp1_label"), "Tctl\n")?;
+/// fs::write(chip.join("temp1_input"), "48250\n")?;
+///
+/// assert_eq!(cpu_selector(&discover(&root)?), Some("k10temp/Tctl"));
+/// assert_eq!(cpu_selector(&[]), None);
+/// # fs::remove_dir_all(&root)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn cpu_selector(sensors: &[Sensor]) -> Option<&'static str> {
+    CPU_SELECTORS
+        .iter()
+        .copied()
+        .find(|selector| sensors.iter().any(|sensor| sensor.matches(selector)))
+}
+
+/// Returns every temperature and fan sensor found within the ```hwmon```
+/// and ```thermal``` directories of the given ```root```, which is usually
+/// ```/sys/class```.
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if neither directory can be
+/// opened for reading.
+pub fn discover(root: &Path) -> Result<Vec<Sensor>, Box<dyn Error>> {
+    let hwmon = discover_hwmon(&root.join("hwmon"));
+    let thermal = discover_thermal(&root.join("thermal"));
+
+    match (hwmon, thermal) {
+        (Err(err), Err(_)) => Err(err),
+        (hwmon, thermal) => Ok(hwmon
+            .unwrap_or_default()
+            .into_iter()
+            .chain(thermal.unwrap_or_default())
+            .collect()),
+    }
+}
+
+fn discover_hwmon(dir: &Path) -> Result<Vec<Sensor>, Box<dyn Error>> {
+    let mut sensors = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let chip_dir = entry?.path();
+        let chip = match read_string(&chip_dir.join("name")) {
+            Ok(chip) => chip,
+            Err(_) => continue,
+        };
+
+        for entry in fs::read_dir(&chip_dir)? {
+            let file_name = entry?.file_name();
+            let file_name = file_name.to_string_lossy();
+
+            let (prefix, kind) = if file_name.starts_with("temp") {
+                ("temp", SensorKind::Temperature)
+            } else if file_name.starts_with("fan") {
+                ("fan", SensorKind::Fan)
+            } else {
+                continue;
+            };
+
+            let name = match file_name.strip_suffix("_input") {
+                Some(name) if name[prefix.len()..].parse::<u32>().is_ok() => {
+                    name
+                }
+                _ => continue,
+            };
+
+            let critical_celsius =
+                read_string(&chip_dir.join(format!("{}_crit", name)))
+                    .ok()
+                    .and_then(|value| value.parse::<f32>().ok())
+                    .map(|value| value / 1000.0);
+
+            sensors.push(Sensor {
+                chip: chip.clone(),
+                label: read_string(&chip_dir.join(format!("{}_label", name)))
+                    .unwrap_or_else(|_| String::from(name)),
+                kind,
+                critical_celsius,
+                input: chip_dir.join(&*file_name),
+            });
+        }
+    }
+
+    sensors.sort_by(|a, b| a.input.cmp(&b.input));
+
+    Ok(sensors)
+}
+
+fn discover_thermal(dir: &Path) -> Result<Vec<Sensor>, Box<dyn Error>> {
+    let mut sensors = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let zone_dir = entry?.path();
+        let is_zone = zone_dir.file_name().is_some_and(|name| {
+            name.to_string_lossy().starts_with("thermal_zone")
+        });
+
+        if !is_zone {
+            continue;
+        }
+
+        let label = match read_string(&zone_dir.join("type")) {
+            Ok(label) => label,
+            Err(_) => continue,
+        };
+
+        let critical_celsius = (0..)
+            .map(|trip| zone_dir.join(format!("trip_point_{}_type", trip)))
+            .take_while(|path| path.exists())
+            .enumerate()
+            .find(|(_, path)| {
+                read_string(path).is_ok_and(|kind| kind == "critical")
+            })
+            .and_then(|(trip, _)| {
+                read_string(&zone_dir.join(format!("trip_point_{}_temp", trip)))
+                    .ok()
+            })
+            .and_then(|value| value.parse::<f32>().ok())
+            .map(|value| value / 1000.0);
+
+        sensors.push(Sensor {
+            chip: String::from("thermal"),
+            label,
+            kind: SensorKind::Temperature,
+            critical_celsius,
+            input: zone_dir.join("temp"),
+        });
+    }
+
+    sensors.sort_by(|a, b| a.input.cmp(&b.input));
+
+    Ok(sensors)
+}
@@ -2,6 +2,7 @@
 //! dwmstatus aims to listen for.
 
 use std::io::Error;
+use std::os::raw::c_int;
 use std::sync::{atomic::AtomicBool, Arc};
 
 use signal_hook::consts::signal::*;
@@ -12,7 +13,12 @@ use signal_hook::iterator::SignalsInfo;
 
 /// Returns the signals that be iterated through in order to
 /// persistently listen for them and act in response.
-pub fn get_signals() -> Result<SignalsInfo<WithOrigin>, Error> {
+///
+/// The given ```action_signals``` are listened for in addition to the
+/// default ones.
+pub fn get_signals(
+    action_signals: &[c_int],
+) -> Result<SignalsInfo<WithOrigin>, Error> {
     let term_now = Arc::new(AtomicBool::new(false));
 
     for sig in TERM_SIGNALS {
@@ -20,12 +26,17 @@ pub fn get_signals() -> Result<SignalsInfo<WithOrigin>, Error> {
         flag::register(*sig, Arc::clone(&term_now))?;
     }
 
-    let mut sigs = vec![
-        SIGUSR1,
-    ];
+    let mut sigs = vec![SIGUSR1];
+    sigs.extend(action_signals);
     sigs.extend(TERM_SIGNALS);
 
     let signals = SignalsInfo::<WithOrigin>::new(&sigs)?;
 
     Ok(signals)
 }
+
+/// Returns the real-time signal ```SIGRTMIN+offset```, which is what tools such
+/// as ```pkill -RTMIN+offset dwmstatus``` or dwm's statuscmd patch send.
+pub fn action_signal(offset: c_int) -> c_int {
+    libc::SIGRTMIN() + offset
+}
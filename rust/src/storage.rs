@@ -0,0 +1,345 @@
+//! The ```storage``` module provides a struct containing the health of the
+//! system's software RAID arrays and pooled filesystems, such as degraded or
+//! resyncing md arrays, btrfs device errors and unhealthy zfs pools.
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{sysfs::read_string, Status};
+
+pub struct Storage {
+    pub arrays: Vec<Array>,
+    pub btrfs_devices: Vec<BtrfsDevice>,
+    pub zfs_pools: Vec<ZfsPool>,
+    root: PathBuf,
+}
+
+/// An md array found in ```/proc/mdstat```.
+pub struct Array {
+    pub name: String,
+    pub active: bool,
+    /// The number of devices the array should have, and the number it has.
+    pub devices: (u32, u32),
+    pub failed_devices: u32,
+    /// The ongoing sync action, such as ```resync```, ```recovery```,
+    /// ```reshape``` or ```check```, along with its progress percentage and
+    /// estimated minutes left, if any.
+    pub sync: Option<(String, Option<f32>, Option<f32>)>,
+}
+
+/// A btrfs device's error counters, as found in
+/// ```/sys/fs/btrfs/<uuid>/devinfo/<devid>/error_stats```.
+pub struct BtrfsDevice {
+    pub filesystem: String,
+    pub id: String,
+    pub errors: u64,
+}
+
+/// A zfs pool's state, as found in ```/proc/spl/kstat/zfs/<pool>/state```.
+pub struct ZfsPool {
+    pub name: String,
+    pub state: String,
+}
+
+impl Array {
+    pub fn degraded(&self) -> bool {
+        !self.active
+            || self.devices.1 < self.devices.0
+            || self.failed_devices > 0
+    }
+}
+
+impl Storage {
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/proc/mdstat``` or any of
+    /// the btrfs and zfs status files exist but can't be read or parsed. Their
+    /// absence simply means there's nothing to report on.
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        Storage::with_root("/")
+    }
+
+    /// Same as ```Storage::new```, except that every file is looked up
+    /// within ```root``` instead of ```/```.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{fs, process};
+    /// use dwmstatus::storage::Storage;
+    ///
+    /// let root = std::env::temp_dir()
+    ///     .join(format!("dwmstatus-storage-{}", process::id()));
+    /// let device = root.join("sys/fs/btrfs/0123-abcd/devinfo/1");
+    /// let pool = root.join("proc/spl/kstat/zfs/tank");
+    /// fs::create_dir_all(&device)?;
+    /// fs::create_dir_all(&pool)?;
+    ///
+    /// fs::write(
+    ///     root.join("proc/mdstat"),
+    ///     "Personalities : [raid1]\nunused devices: <none>\n",
+    /// )?;
+    /// fs::write(root.join("sys/fs/btrfs/0123-abcd/label"), "data\n")?;
+    /// fs::write(
+    ///     device.join("error_stats"),
+    ///     "write_errs 0\nread_errs 0\nflush_errs 0\n\
+    ///      corruption_errs 0\ngeneration_errs 0\n",
+    /// )?;
+    /// fs::write(pool.join("state"), "ONLINE\n")?;
+    ///
+    /// let storage = Storage::with_root(&root)?;
+    /// assert!(storage.healthy());
+    ///
+    /// fs::write(
+    ///     device.join("error_stats"),
+    ///     "write_errs 0\nread_errs 3\ncorruption_errs 1\n",
+    /// )?;
+    /// fs::write(pool.join("state"), "DEGRADED\n")?;
+    ///
+    /// let storage = Storage::with_root(&root)?;
+    /// assert!(!storage.healthy());
+    /// assert_eq!(storage.btrfs_devices[0].filesystem, "data");
+    /// assert_eq!(storage.btrfs_devices[0].errors, 4);
+    /// assert_eq!(storage.zfs_pools[0].state, "DEGRADED");
+    /// # fs::remove_dir_all(&root)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_root<P: AsRef<Path>>(root: P) -> Result<Self, Box<dyn Error>> {
+        let mut storage = Storage {
+            arrays: Vec::new(),
+            btrfs_devices: Vec::new(),
+            zfs_pools: Vec::new(),
+            root: root.as_ref().to_path_buf(),
+        };
+
+        storage.update()?;
+
+        Ok(storage)
+    }
+
+    /// Returns whether there's nothing worth reporting, meaning no array is
+    /// degraded or syncing, no btrfs device has errors and every zfs pool is
+    /// online.
+    pub fn healthy(&self) -> bool {
+        self.arrays
+            .iter()
+            .all(|array| !array.degraded() && array.sync.is_none())
+            && self.btrfs_devices.iter().all(|device| device.errors == 0)
+            && self.zfs_pools.iter().all(|pool| pool.state == "ONLINE")
+    }
+
+    /// Returns whether anything requires immediate attention, which excludes
+    /// arrays that are merely syncing.
+    pub fn urgent(&self) -> bool {
+        self.arrays.iter().any(|array| array.degraded())
+            || self.btrfs_devices.iter().any(|device| device.errors > 0)
+            || self.zfs_pools.iter().any(|pool| pool.state != "ONLINE")
+    }
+
+    fn read_btrfs_devices(&self) -> Result<Vec<BtrfsDevice>, Box<dyn Error>> {
+        let mut devices = Vec::new();
+
+        for filesystem_dir in read_dir_paths(&self.root.join("sys/fs/btrfs"))? {
+            let uuid = file_name(&filesystem_dir);
+            let filesystem = read_string(&filesystem_dir.join("label"))
+                .ok()
+                .filter(|label| !label.is_empty())
+                .unwrap_or(uuid);
+
+            for device_dir in read_dir_paths(&filesystem_dir.join("devinfo"))? {
+                // Kernels prior to 5.14 don't expose error counters in sysfs
+                let contents =
+                    match fs::read_to_string(device_dir.join("error_stats")) {
+                        Ok(contents) => contents,
+                        Err(_) => continue,
+                    };
+
+                devices.push(BtrfsDevice {
+                    filesystem: filesystem.clone(),
+                    id: file_name(&device_dir),
+                    errors: parse_btrfs_error_stats(&contents)?,
+                });
+            }
+        }
+
+        Ok(devices)
+    }
+
+    fn read_zfs_pools(&self) -> Result<Vec<ZfsPool>, Box<dyn Error>> {
+        let mut pools = Vec::new();
+
+        for pool_dir in read_dir_paths(&self.root.join("proc/spl/kstat/zfs"))? {
+            let state = match read_string(&pool_dir.join("state")) {
+                Ok(state) => state,
+                Err(_) => continue,
+            };
+
+            pools.push(ZfsPool {
+                name: file_name(&pool_dir),
+                state,
+            });
+        }
+
+        Ok(pools)
+    }
+}
+
+impl Status for Storage {
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if any of the existing status
+    /// files can't be read or parsed.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        self.arrays = match fs::read_to_string(self.root.join("proc/mdstat")) {
+            Ok(contents) => parse_mdstat(&contents)?,
+            Err(_) => Vec::new(),
+        };
+        self.btrfs_devices = self.read_btrfs_devices()?;
+        self.zfs_pools = self.read_zfs_pools()?;
+
+        Ok(())
+    }
+}
+
+/// Parses the arrays found in the given ```/proc/mdstat``` contents.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::storage::parse_mdstat;
+///
+/// let arrays = parse_mdstat(
+///     "Personalities : [raid1] [raid6] [raid5] [raid4]\n\
+///      md0 : active raid1 sdb1[1] sda1[0]\n      \
+///            976630464 blocks super 1.2 [2/2] [UU]\n      \
+///            bitmap: 0/8 pages [0KB], 65536KB chunk\n\
+///      \n\
+///      md1 : active raid5 sdc1[3] sdd1[1] sde1[0](F)\n      \
+///            1953260544 blocks super 1.2 level 5, 512k chunk, algorithm 2 [3/2] [UU_]\n      \
+///            [=>...................]  recovery =  8.5% (83064320/976630272) finish=87.3min speed=170502K/sec\n\
+///      \n\
+///      unused devices: <none>\n",
+/// )?;
+///
+/// assert!(!arrays[0].degraded());
+/// assert!(arrays[0].sync.is_none());
+/// assert!(arrays[1].degraded());
+/// assert_eq!(arrays[1].devices, (3, 2));
+/// assert_eq!(arrays[1].failed_devices, 1);
+/// assert_eq!(
+///     arrays[1].sync,
+///     Some((String::from("recovery"), Some(8.5), Some(87.3))),
+/// );
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if an array's device counts
+/// can't be parsed.
+pub fn parse_mdstat(contents: &str) -> Result<Vec<Array>, Box<dyn Error>> {
+    let mut arrays: Vec<Array> = Vec::new();
+
+    for line in contents.lines() {
+        if let Some((name, description)) = line.split_once(" : ") {
+            if !name.starts_with("md") {
+                continue;
+            }
+
+            let mut values = description.split_whitespace();
+
+            arrays.push(Array {
+                name: String::from(name.trim()),
+                active: values.next() == Some("active"),
+                devices: (0, 0),
+                failed_devices: values
+                    .filter(|value| value.ends_with("(F)"))
+                    .count() as u32,
+                sync: None,
+            });
+            continue;
+        }
+
+        let array = match arrays.last_mut() {
+            Some(array) if line.starts_with(' ') => array,
+            _ => continue,
+        };
+
+        for value in line.split_whitespace() {
+            let counts = value
+                .strip_prefix('[')
+                .and_then(|value| value.strip_suffix(']'))
+                .and_then(|value| value.split_once('/'));
+
+            if let Some((expected, present)) = counts {
+                array.devices = (expected.parse()?, present.parse()?);
+            }
+        }
+
+        for action in ["resync", "recovery", "reshape", "check", "repair"] {
+            let rest = match line.split_once(action) {
+                Some((_, rest)) if rest.trim_start().starts_with('=') => rest,
+                _ => continue,
+            };
+
+            // Delayed or pending syncs have neither a percentage nor an ETA
+            let percent = rest
+                .trim_start_matches(|c: char| c == '=' || c.is_whitespace())
+                .split('%')
+                .next()
+                .and_then(|percent| percent.parse().ok());
+            let minutes = rest
+                .split_once("finish=")
+                .and_then(|(_, finish)| finish.split("min").next())
+                .and_then(|minutes| minutes.parse().ok());
+
+            array.sync = Some((String::from(action), percent, minutes));
+            break;
+        }
+    }
+
+    Ok(arrays)
+}
+
+/// Returns the sum of the counters found in the given btrfs ```error_stats```
+/// contents.
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if any of the counters isn't a
+/// valid number.
+pub fn parse_btrfs_error_stats(contents: &str) -> Result<u64, Box<dyn Error>> {
+    let mut errors = 0;
+
+    for line in contents.lines() {
+        if let Some((_, value)) = line.split_once(' ') {
+            errors += value.trim().parse::<u64>()?;
+        }
+    }
+
+    Ok(errors)
+}
+
+/// Returns the paths of the entries of ```dir```, or nothing if it doesn't
+/// exist.
+fn read_dir_paths(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+
+    Ok(paths)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
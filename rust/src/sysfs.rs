@@ -0,0 +1,14 @@
+//! The ```sysfs``` module provides helper functions for reading the single
+//! value files found in sysfs and procfs.
+
+use std::{error::Error, fs, path::Path};
+
+/// Returns the contents of the file at ```path```, without its trailing
+/// newline.
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if the file can't be read.
+pub fn read_string(path: &Path) -> Result<String, Box<dyn Error>> {
+    Ok(fs::read_to_string(path)?.trim().to_string())
+}
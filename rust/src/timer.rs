@@ -0,0 +1,378 @@
+//! The ```timer``` module provides a timer waking on the boundaries of a
+//! period of the wall clock, such as every minute, which also wakes as soon
+//! as the wall clock is set or the local time zone changes.
+//!
+//! The timer is a ```timerfd``` armed with ```TFD_TIMER_CANCEL_ON_SET```, so
+//! that discontinuous changes of the wall clock, such as those made by NTP or
+//! by hand, cancel it. Since it uses absolute expiration times, it also fires
+//! right after resuming from suspend. Time zone changes are noticed through
+//! ```inotify``` events about ```/etc/localtime```. Another thread may wake
+//! the timer early through an ```eventfd```, such as to stop waiting on it.
+
+use std::{
+    ffi::CString,
+    fs::File,
+    io::{self, Read, Write},
+    mem,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    ptr,
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::timesource::{SystemTimeSource, TimeSource};
+
+static LOCALTIME_DIR: &str = "/etc";
+static LOCALTIME_NAME: &str = "localtime";
+
+pub struct BoundaryTimer {
+    /// The period whose boundaries the timer wakes on, such as a minute.
+    pub period: Duration,
+    timer: File,
+    inotify: Option<File>,
+    eventfd: Arc<File>,
+    time: Arc<dyn TimeSource>,
+}
+
+/// Wakes a ```BoundaryTimer``` from another thread.
+#[derive(Clone)]
+pub struct Waker {
+    eventfd: Arc<File>,
+}
+
+/// The reason a timer woke.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Wake {
+    Boundary,
+    ClockChanged,
+    TimeZoneChanged,
+    Woken,
+}
+
+impl BoundaryTimer {
+    /// Where ```period``` is a whole number of seconds, such as one second
+    /// or one minute.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use dwmstatus::timer::{BoundaryTimer, Wake};
+    ///
+    /// let mut timer = BoundaryTimer::new(Duration::from_secs(1))?;
+    ///
+    /// assert_eq!(timer.wait()?, Wake::Boundary);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if either the ```timerfd``` or
+    /// the ```eventfd``` can't be created. Failing to watch ```/etc``` only
+    /// means that time zone changes won't wake the timer.
+    pub fn new(period: Duration) -> io::Result<Self> {
+        BoundaryTimer::with_time_source(period, Arc::new(SystemTimeSource))
+    }
+
+    /// Same as ```BoundaryTimer::new```, except that boundaries are computed
+    /// from the wall clock of the given ```time``` source. The timer itself
+    /// still expires according to the system's wall clock, so that boundaries
+    /// which already passed on the latter expire right away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{sync::Arc, time::Duration};
+    /// use chrono::{TimeZone, Utc};
+    /// use dwmstatus::{
+    ///     timer::{BoundaryTimer, Wake},
+    ///     timesource::FakeTimeSource,
+    /// };
+    ///
+    /// let start = Utc.with_ymd_and_hms(2024, 3, 31, 0, 59, 0).unwrap();
+    /// let time = Arc::new(FakeTimeSource::new(start));
+    /// let mut timer =
+    ///     BoundaryTimer::with_time_source(Duration::from_secs(60), time)?;
+    ///
+    /// assert_eq!(timer.wait()?, Wake::Boundary);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` for the same reasons
+    /// as ```BoundaryTimer::new```.
+    pub fn with_time_source(
+        period: Duration,
+        time: Arc<dyn TimeSource>,
+    ) -> io::Result<Self> {
+        if period.as_secs() == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "The timer's period must be at least a second!",
+            ));
+        }
+
+        let fd = unsafe {
+            libc::timerfd_create(libc::CLOCK_REALTIME, libc::TFD_CLOEXEC)
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let timer = File::from(unsafe { OwnedFd::from_raw_fd(fd) });
+
+        let fd =
+            unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(BoundaryTimer {
+            period,
+            timer,
+            inotify: watch_localtime().ok(),
+            eventfd: Arc::new(File::from(unsafe { OwnedFd::from_raw_fd(fd) })),
+            time,
+        })
+    }
+
+    /// Returns a ```Waker``` through which another thread may wake the timer,
+    /// in which case ```wait``` returns ```Wake::Woken```.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{thread, time::Duration};
+    /// use dwmstatus::timer::{BoundaryTimer, Wake};
+    ///
+    /// let mut timer = BoundaryTimer::new(Duration::from_secs(3600))?;
+    /// let waker = timer.waker();
+    ///
+    /// thread::spawn(move || waker.wake());
+    /// assert_eq!(timer.wait()?, Wake::Woken);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn waker(&self) -> Waker {
+        Waker {
+            eventfd: self.eventfd.clone(),
+        }
+    }
+
+    /// Blocks until either the next boundary of the period, the wall clock
+    /// being set, the local time zone changing, or the timer being woken, and
+    /// returns which one happened.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the timer can't be armed or
+    /// waited on.
+    pub fn wait(&mut self) -> io::Result<Wake> {
+        let boundary =
+            next_boundary(since_epoch(self.time.as_ref()), self.period);
+        let spec = libc::itimerspec {
+            it_interval: libc::timespec {
+                tv_sec: 0,
+                tv_nsec: 0,
+            },
+            it_value: libc::timespec {
+                tv_sec: boundary.as_secs() as libc::time_t,
+                tv_nsec: 0,
+            },
+        };
+
+        if unsafe {
+            libc::timerfd_settime(
+                self.timer.as_raw_fd(),
+                libc::TFD_TIMER_ABSTIME | libc::TFD_TIMER_CANCEL_ON_SET,
+                &spec,
+                ptr::null_mut(),
+            )
+        } != 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+
+        loop {
+            let mut fds = vec![
+                libc::pollfd {
+                    fd: self.timer.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+                libc::pollfd {
+                    fd: self.eventfd.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+            ];
+            if let Some(inotify) = &self.inotify {
+                fds.push(libc::pollfd {
+                    fd: inotify.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                });
+            }
+
+            if unsafe {
+                libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1)
+            } < 0
+            {
+                let err = io::Error::last_os_error();
+
+                match err.kind() {
+                    io::ErrorKind::Interrupted => continue,
+                    _ => return Err(err),
+                }
+            }
+
+            if fds[1].revents & libc::POLLIN != 0 {
+                let mut counter = [0u8; 8];
+                let _ = self.eventfd.as_ref().read(&mut counter);
+
+                return Ok(Wake::Woken);
+            }
+
+            let watched =
+                fds.get(2).is_some_and(|fd| fd.revents & libc::POLLIN != 0);
+
+            if watched && self.read_inotify() {
+                return Ok(Wake::TimeZoneChanged);
+            }
+
+            if fds[0].revents & libc::POLLIN != 0 {
+                let mut expirations = [0u8; 8];
+
+                return match self.timer.read(&mut expirations) {
+                    Ok(_) => Ok(Wake::Boundary),
+                    Err(err) if err.raw_os_error() == Some(libc::ECANCELED) => {
+                        Ok(Wake::ClockChanged)
+                    }
+                    Err(err) => Err(err),
+                };
+            }
+        }
+    }
+
+    /// Reads the pending ```inotify``` events, and returns whether any of
+    /// them is about ```/etc/localtime```.
+    fn read_inotify(&mut self) -> bool {
+        let inotify = match &mut self.inotify {
+            Some(inotify) => inotify,
+            None => return false,
+        };
+        let mut buffer = [0u8; 4096];
+        let mut changed = false;
+
+        // The descriptor is non-blocking, so reading stops once drained
+        while let Ok(len) = inotify.read(&mut buffer) {
+            if len == 0 {
+                break;
+            }
+
+            changed |= parse_event_names(&buffer[..len])
+                .iter()
+                .any(|name| name == LOCALTIME_NAME);
+        }
+
+        changed
+    }
+}
+
+impl Waker {
+    /// Makes the timer's current or next ```wait``` return right away.
+    pub fn wake(&self) {
+        let _ = self.eventfd.as_ref().write(&1u64.to_ne_bytes());
+    }
+}
+
+/// Returns the time since the epoch of the boundary of ```period``` following
+/// ```now```, itself a time since the epoch.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use dwmstatus::timer::next_boundary;
+///
+/// let minute = Duration::from_secs(60);
+///
+/// let boundary = next_boundary(Duration::from_millis(119_500), minute);
+/// assert_eq!(boundary.as_secs(), 120);
+/// assert_eq!(next_boundary(Duration::from_secs(120), minute).as_secs(), 180);
+/// ```
+pub fn next_boundary(now: Duration, period: Duration) -> Duration {
+    let period = period.as_secs().max(1);
+
+    Duration::from_secs((now.as_secs() / period + 1) * period)
+}
+
+/// Returns the time elapsed since the epoch according to the wall clock of
+/// the given ```time``` source.
+pub fn since_epoch(time: &dyn TimeSource) -> Duration {
+    Duration::from_millis(time.now().timestamp_millis().max(0) as u64)
+}
+
+/// Returns the file names found in the given buffer of ```inotify```
+/// events, which are padded with null bytes.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::timer::parse_event_names;
+///
+/// let mut events = Vec::new();
+/// // The watch descriptor, mask, cookie and name length
+/// for field in [1u32, 0x100, 0, 16] {
+///     events.extend_from_slice(&field.to_ne_bytes());
+/// }
+/// events.extend_from_slice(b"localtime\0\0\0\0\0\0\0");
+///
+/// assert_eq!(parse_event_names(&events), ["localtime"]);
+/// ```
+pub fn parse_event_names(buffer: &[u8]) -> Vec<String> {
+    let header = mem::size_of::<libc::inotify_event>();
+    let mut names = Vec::new();
+    let mut rest = buffer;
+
+    while rest.len() >= header {
+        let len = u32::from_ne_bytes([rest[12], rest[13], rest[14], rest[15]])
+            as usize;
+        let name = match rest.get(header..header + len) {
+            Some(name) => name,
+            None => break,
+        };
+        let name = name.split(|byte| *byte == 0).next().unwrap_or_default();
+
+        if !name.is_empty() {
+            names.push(String::from_utf8_lossy(name).into_owned());
+        }
+        rest = &rest[header + len..];
+    }
+
+    names
+}
+
+/// Watches ```/etc``` rather than ```/etc/localtime``` itself, since the
+/// latter is usually a symbolic link replaced as a whole.
+fn watch_localtime() -> io::Result<File> {
+    let fd =
+        unsafe { libc::inotify_init1(libc::IN_CLOEXEC | libc::IN_NONBLOCK) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let inotify = File::from(unsafe { OwnedFd::from_raw_fd(fd) });
+
+    let path = CString::new(LOCALTIME_DIR)?;
+    let mask = libc::IN_CREATE
+        | libc::IN_MOVED_TO
+        | libc::IN_CLOSE_WRITE
+        | libc::IN_DELETE;
+    if unsafe {
+        libc::inotify_add_watch(inotify.as_raw_fd(), path.as_ptr(), mask)
+    } < 0
+    {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(inotify)
+}
@@ -0,0 +1,72 @@
+//! The ```timesource``` module provides the ```TimeSource``` trait through
+//! which time-based statuses read the current time, so that a fake source may
+//! be used in place of the system's clocks, such as when testing how a status
+//! behaves across days, months or daylight saving time transitions.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+
+pub trait TimeSource: Send + Sync {
+    /// Returns the current wall clock time, used to display dates and times.
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Returns the current monotonic time, used to measure elapsed time such
+    /// as between two samples of a counter.
+    fn instant(&self) -> Instant;
+}
+
+/// The system's clocks.
+pub struct SystemTimeSource;
+
+/// A time source that only moves when told to.
+pub struct FakeTimeSource {
+    state: Mutex<(DateTime<Utc>, Instant)>,
+}
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+impl FakeTimeSource {
+    /// Where ```now``` is the wall clock time the source starts at.
+    pub fn new(now: DateTime<Utc>) -> Self {
+        FakeTimeSource {
+            state: Mutex::new((now, Instant::now())),
+        }
+    }
+
+    /// Moves both the wall clock and the monotonic clock forward.
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state.lock().unwrap();
+
+        state.0 += chrono::Duration::from_std(duration)
+            .unwrap_or(chrono::Duration::MAX);
+        state.1 += duration;
+    }
+
+    /// Sets the wall clock without moving the monotonic clock, as happens
+    /// when the system's time is set.
+    pub fn set(&self, now: DateTime<Utc>) {
+        self.state.lock().unwrap().0 = now;
+    }
+}
+
+impl TimeSource for FakeTimeSource {
+    fn now(&self) -> DateTime<Utc> {
+        self.state.lock().unwrap().0
+    }
+
+    fn instant(&self) -> Instant {
+        self.state.lock().unwrap().1
+    }
+}
@@ -0,0 +1,262 @@
+//! The ```top``` module provides a struct containing the processes currently
+//! consuming the most cpu time and resident memory, as found by sampling
+//! ```/proc/[pid]/stat``` and ```/proc/[pid]/status```.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    cpu::Jiffies,
+    timesource::{SystemTimeSource, TimeSource},
+    Status,
+};
+
+pub struct Top {
+    /// The name of the process that used the most cpu time since the
+    /// previous sample, which is empty until a second sample was taken.
+    pub cpu_name: String,
+    pub cpu_pid: u32,
+    /// The percentage of the total cpu time used by the process since the
+    /// previous sample, same as ```cpu::Cpu```'s utilization.
+    pub cpu_utilization: f32,
+    pub memory_name: String,
+    pub memory_pid: u32,
+    pub memory_bytes: u64,
+    /// The names of processes that should never be reported.
+    pub excluded: Vec<String>,
+    /// The minimum time between samples, since reading every process's files
+    /// is comparatively expensive.
+    pub interval: Duration,
+    process_ticks: HashMap<u32, u64>,
+    total_jiffies: u64,
+    sampled_at: Instant,
+    time: Arc<dyn TimeSource>,
+}
+
+impl Top {
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use dwmstatus::top::Top;
+    ///
+    /// let top_status =
+    ///     Top::new(&["dwmstatus", "Xorg"], Duration::from_secs(5));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the initial sample can't be
+    /// taken because ```/proc``` can't be read.
+    pub fn new(
+        excluded: &[&str],
+        interval: Duration,
+    ) -> Result<Self, Box<dyn Error>> {
+        Top::with_time_source(excluded, interval, Arc::new(SystemTimeSource))
+    }
+
+    /// Same as ```Top::new```, except that the interval between samples is
+    /// measured using the given ```time``` source rather than the system's
+    /// clocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{sync::Arc, time::Duration};
+    /// use chrono::Utc;
+    /// use dwmstatus::{timesource::FakeTimeSource, top::Top, Status};
+    ///
+    /// let time = Arc::new(FakeTimeSource::new(Utc::now()));
+    /// let mut top =
+    ///     Top::with_time_source(&[], Duration::from_secs(5), time.clone())?;
+    ///
+    /// // The cpu time of processes is only known from the second sample on
+    /// time.advance(Duration::from_secs(4));
+    /// top.update()?;
+    /// assert!(top.cpu_name.is_empty());
+    ///
+    /// time.advance(Duration::from_secs(1));
+    /// top.update()?;
+    /// assert!(!top.cpu_name.is_empty());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` for the same reasons
+    /// as ```Top::new```.
+    pub fn with_time_source(
+        excluded: &[&str],
+        interval: Duration,
+        time: Arc<dyn TimeSource>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut top = Top {
+            cpu_name: String::new(),
+            cpu_pid: 0,
+            cpu_utilization: 0.0,
+            memory_name: String::new(),
+            memory_pid: 0,
+            memory_bytes: 0,
+            excluded: excluded.iter().map(|name| String::from(*name)).collect(),
+            interval,
+            process_ticks: HashMap::new(),
+            total_jiffies: 0,
+            sampled_at: time.instant(),
+            time,
+        };
+
+        top.sample()?;
+
+        Ok(top)
+    }
+
+    fn sample(&mut self) -> Result<(), Box<dyn Error>> {
+        let stat = fs::read_to_string("/proc/stat")?;
+        let total_jiffies =
+            Jiffies::parse(stat.lines().next().unwrap_or(""))?.total();
+        let total_delta = total_jiffies.saturating_sub(self.total_jiffies);
+
+        let mut process_ticks = HashMap::new();
+        let mut top_cpu: Option<(u32, String, u64)> = None;
+        let mut top_memory: Option<(u32, String, u64)> = None;
+
+        for entry in fs::read_dir("/proc")? {
+            let pid: u32 = match entry?
+                .file_name()
+                .to_str()
+                .and_then(|pid| pid.parse().ok())
+            {
+                Some(pid) => pid,
+                None => continue,
+            };
+
+            // Processes may exit at any point while being sampled
+            let (name, ticks) =
+                match fs::read_to_string(format!("/proc/{}/stat", pid))
+                    .ok()
+                    .and_then(|contents| parse_stat(&contents))
+                {
+                    Some(stat) => stat,
+                    None => continue,
+                };
+
+            if self.excluded.contains(&name) {
+                continue;
+            }
+
+            process_ticks.insert(pid, ticks);
+
+            // Processes missing from the previous sample, as every process
+            // is on the first one, have no cpu time to compare against
+            if let Some(previous_ticks) = self.process_ticks.get(&pid) {
+                let delta = ticks.saturating_sub(*previous_ticks);
+
+                if top_cpu.as_ref().is_none_or(|(_, _, max)| delta > *max) {
+                    top_cpu = Some((pid, name.clone(), delta));
+                }
+            }
+
+            // Kernel threads have no resident memory
+            let rss_bytes =
+                match fs::read_to_string(format!("/proc/{}/status", pid))
+                    .ok()
+                    .and_then(|contents| parse_rss_bytes(&contents))
+                {
+                    Some(rss_bytes) => rss_bytes,
+                    None => continue,
+                };
+
+            if top_memory
+                .as_ref()
+                .is_none_or(|(_, _, max)| rss_bytes > *max)
+            {
+                top_memory = Some((pid, name, rss_bytes));
+            }
+        }
+
+        if let Some((pid, name, delta)) = top_cpu {
+            self.cpu_pid = pid;
+            self.cpu_name = name;
+            self.cpu_utilization = if total_delta == 0 {
+                0.0
+            } else {
+                (100.0 * delta as f64 / total_delta as f64) as f32
+            };
+        }
+
+        if let Some((pid, name, rss_bytes)) = top_memory {
+            self.memory_pid = pid;
+            self.memory_name = name;
+            self.memory_bytes = rss_bytes;
+        }
+
+        self.process_ticks = process_ticks;
+        self.total_jiffies = total_jiffies;
+        self.sampled_at = self.time.instant();
+
+        Ok(())
+    }
+}
+
+impl Status for Top {
+    /// Does nothing until ```interval``` elapsed since the previous sample.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/proc```
+    /// or ```/proc/stat``` can't be read.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        if self
+            .time
+            .instant()
+            .saturating_duration_since(self.sampled_at)
+            < self.interval
+        {
+            return Ok(());
+        }
+
+        self.sample()
+    }
+}
+
+/// Returns the name and total cpu ticks, user and system combined, found in
+/// the given ```/proc/[pid]/stat``` contents.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::top::parse_stat;
+///
+/// let contents = "1234 (Web Content (x)) S 1 1234 1234 0 -1 4194560 \
+///                 1200 0 0 0 250 50 0 0 20 0 30 0 5000 123456 789 ...";
+///
+/// assert_eq!(
+///     parse_stat(contents),
+///     Some((String::from("Web Content (x)"), 300)),
+/// );
+/// ```
+pub fn parse_stat(contents: &str) -> Option<(String, u64)> {
+    // The name may contain spaces and parentheses itself
+    let start = contents.find('(')?;
+    let end = contents.rfind(')')?;
+    let name = contents.get(start + 1..end)?;
+
+    // Fields following the name start at the third one, the process state
+    let mut fields = contents.get(end + 1..)?.split_whitespace().skip(11);
+    let user_ticks: u64 = fields.next()?.parse().ok()?;
+    let system_ticks: u64 = fields.next()?.parse().ok()?;
+
+    Some((String::from(name), user_ticks + system_ticks))
+}
+
+fn parse_rss_bytes(contents: &str) -> Option<u64> {
+    let line = contents.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kibibytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+
+    Some(kibibytes * 1024)
+}
@@ -0,0 +1,522 @@
+//! The ```usage``` module provides a struct containing the amount of data
+//! received and transmitted by the system's network interfaces during the
+//! current day and month.
+//!
+//! Totals are persisted to a state file so that they survive restarts as well
+//! as reboots, and roll over at configurable day and month boundaries.
+
+use std::{
+    collections::HashMap,
+    env,
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime};
+
+use crate::{
+    net, network,
+    output::Thresholds,
+    timesource::{SystemTimeSource, TimeSource},
+    Status,
+};
+
+pub struct Usage {
+    pub today: Totals,
+    pub month: Totals,
+    /// The hour at which days start, such as ```4``` for late nights to count
+    /// towards the previous day.
+    pub day_start_hour: u32,
+    /// The day at which months start, such as a plan's billing day, from
+    /// ```1``` to ```28```.
+    pub month_start_day: u32,
+    /// The monthly amount of data, received and transmitted combined, that
+    /// the plan allows.
+    pub cap_bytes: Option<u64>,
+    /// The percentages of ```cap_bytes``` at which to warn.
+    pub thresholds: Thresholds,
+    /// The minimum time between writes of the state file.
+    pub save_interval: Duration,
+    pub path: PathBuf,
+    interface_names: Option<Vec<String>>,
+    counters: HashMap<String, (u64, u64)>,
+    dev_path: PathBuf,
+    route_path: PathBuf,
+    day_start: NaiveDate,
+    month_start: NaiveDate,
+    boot_id: String,
+    saved_at: Option<Instant>,
+    time: Arc<dyn TimeSource>,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct Totals {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+impl Totals {
+    pub fn total(&self) -> u64 {
+        self.rx_bytes + self.tx_bytes
+    }
+}
+
+impl Usage {
+    /// Where ```interface_names``` are the interfaces whose data counts
+    /// towards the totals, or ```None``` to count whichever interface
+    /// currently has the default route, such as a tethered phone's ```usb0```
+    /// while it's plugged in. Totals are persisted
+    /// in ```$XDG_STATE_HOME/dwmstatus/usage```.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use dwmstatus::usage::Usage;
+    ///
+    /// let usage_status = Usage::new(Some(&["wlp4s0", "usb0"]));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if neither ```$XDG_STATE_HOME```
+    /// nor ```$HOME``` are set, or if the state file exists but can't be read.
+    pub fn new(
+        interface_names: Option<&[&str]>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let state_home =
+            match env::var_os("XDG_STATE_HOME").filter(|dir| !dir.is_empty()) {
+                Some(dir) => PathBuf::from(dir),
+                None => env::var_os("HOME")
+                    .map(|home| Path::new(&home).join(".local/state"))
+                    .ok_or("No $XDG_STATE_HOME or $HOME found!")?,
+            };
+
+        Usage::with_paths(
+            interface_names,
+            state_home.join("dwmstatus/usage"),
+            "/proc/net/dev",
+            "/proc/net/route",
+            Arc::new(SystemTimeSource),
+        )
+    }
+
+    /// Same as ```Usage::new```, except that totals are persisted in the
+    /// given ```path```, that counters are sampled from the file
+    /// at ```dev_path``` instead of ```/proc/net/dev```, that the default
+    /// route is looked up in the file at ```route_path``` instead
+    /// of ```/proc/net/route```, and that days, months and saves are timed
+    /// using the given ```time``` source.
+    ///
+    /// Counters saved during the current boot are resumed from, so that data
+    /// used while not running still counts, whereas counters saved during a
+    /// previous boot are discarded since they restarted from zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{fs, process, sync::Arc, time::Duration};
+    /// use chrono::{Local, NaiveDate, TimeZone, Utc};
+    /// use dwmstatus::{
+    ///     timesource::FakeTimeSource,
+    ///     usage::{Totals, Usage},
+    ///     Status,
+    /// };
+    ///
+    /// let dir = std::env::temp_dir()
+    ///     .join(format!("dwmstatus-usage-update-{}", process::id()));
+    /// let (path, dev) = (dir.join("usage"), dir.join("dev"));
+    /// let header = "Inter-|   Receive    |  Transmit\n \
+    ///               face |bytes    packets|bytes    packets\n";
+    /// let usb0 = |counters: &str| format!("{}usb0: {}\n", header, counters);
+    ///
+    /// let start = NaiveDate::from_ymd_opt(2024, 3, 31)
+    ///     .and_then(|day| day.and_hms_opt(23, 0, 0))
+    ///     .and_then(|at| Local.from_local_datetime(&at).single())
+    ///     .unwrap();
+    /// let time = Arc::new(FakeTimeSource::new(start.with_timezone(&Utc)));
+    ///
+    /// fs::create_dir_all(&dir)?;
+    /// fs::write(&dev, usb0("1000 1 0 0 0 0 0 0 100 1 0 0 0 0 0 0"))?;
+    /// let mut usage = Usage::with_paths(
+    ///     Some(&["usb0"]),
+    ///     &path,
+    ///     &dev,
+    ///     "/dev/null",
+    ///     time.clone(),
+    /// )?;
+    /// usage.update()?;
+    ///
+    /// fs::write(&dev, usb0("5000 5 0 0 0 0 0 0 600 6 0 0 0 0 0 0"))?;
+    /// time.advance(Duration::from_secs(30 * 60));
+    /// usage.update()?;
+    /// assert_eq!(usage.today, Totals { rx_bytes: 4000, tx_bytes: 500 });
+    ///
+    /// // Midnight starts both a new day and a new month
+    /// fs::write(&dev, usb0("6000 6 0 0 0 0 0 0 600 6 0 0 0 0 0 0"))?;
+    /// time.advance(Duration::from_secs(60 * 60));
+    /// usage.update()?;
+    /// assert_eq!(usage.today, Totals { rx_bytes: 1000, tx_bytes: 0 });
+    /// assert_eq!(usage.month, Totals { rx_bytes: 1000, tx_bytes: 0 });
+    /// # fs::remove_dir_all(&dir)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the state file exists but
+    /// can't be read.
+    pub fn with_paths<P: AsRef<Path>, Q: AsRef<Path>, R: AsRef<Path>>(
+        interface_names: Option<&[&str]>,
+        path: P,
+        dev_path: Q,
+        route_path: R,
+        time: Arc<dyn TimeSource>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let now = time.now().with_timezone(&Local).naive_local();
+        let mut usage = Usage {
+            today: Totals::default(),
+            month: Totals::default(),
+            day_start_hour: 0,
+            month_start_day: 1,
+            cap_bytes: None,
+            thresholds: Thresholds {
+                warning: 80.0,
+                critical: 95.0,
+            },
+            save_interval: Duration::from_secs(60),
+            path: path.as_ref().to_path_buf(),
+            dev_path: dev_path.as_ref().to_path_buf(),
+            route_path: route_path.as_ref().to_path_buf(),
+            interface_names: interface_names.map(|names| {
+                names.iter().map(|name| String::from(*name)).collect()
+            }),
+            counters: HashMap::new(),
+            day_start: now.date(),
+            month_start: now.date(),
+            boot_id: fs::read_to_string("/proc/sys/kernel/random/boot_id")
+                .map(|boot_id| boot_id.trim().to_string())
+                .unwrap_or_default(),
+            saved_at: None,
+            time,
+        };
+
+        match fs::read_to_string(&usage.path) {
+            Ok(contents) => usage.load(&contents),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                usage.day_start = usage.get_day(now);
+                usage.month_start = usage.get_month(now);
+            }
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(usage)
+    }
+
+    /// The percentage of ```cap_bytes``` used this month, if there's a cap.
+    pub fn cap_percent(&self) -> Option<f32> {
+        self.cap_bytes
+            .filter(|cap_bytes| *cap_bytes > 0)
+            .map(|cap_bytes| {
+                (100.0 * self.month.total() as f64 / cap_bytes as f64) as f32
+            })
+    }
+
+    /// Accumulates the data used since the previous sample using the
+    /// interfaces' received and transmitted byte counters sampled at the
+    /// given local time, along with the name of the interface that currently
+    /// has the default route, rolling totals over first if a later day or
+    /// month started.
+    ///
+    /// Counters that went down were reset, such as when an interface was
+    /// re-created, in which case they count from zero. Times earlier than the
+    /// current day, such as after the clock was set back, count towards it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{process, sync::Arc};
+    /// use chrono::{Local, NaiveDate, TimeZone, Utc};
+    /// use dwmstatus::{
+    ///     timesource::FakeTimeSource,
+    ///     usage::{Totals, Usage},
+    /// };
+    ///
+    /// let at = |day, hour| {
+    ///     NaiveDate::from_ymd_opt(2024, 3, day)
+    ///         .and_then(|day| day.and_hms_opt(hour, 0, 0))
+    ///         .unwrap()
+    /// };
+    /// let start = Local.from_local_datetime(&at(14, 12)).unwrap();
+    /// let time = Arc::new(FakeTimeSource::new(start.with_timezone(&Utc)));
+    ///
+    /// let path = std::env::temp_dir()
+    ///     .join(format!("dwmstatus-usage-{}", process::id()));
+    /// // Counters are only sampled from the given ones below
+    /// let (dev, route) = ("/dev/null", "/dev/null");
+    /// let mut usage =
+    ///     Usage::with_paths(None, &path, dev, route, time.clone())?;
+    /// usage.day_start_hour = 4;
+    /// usage.month_start_day = 15;
+    ///
+    /// let counters = |rx, tx| {
+    ///     vec![
+    ///         (String::from("wlan0"), 9000, 900),
+    ///         (String::from("usb0"), rx, tx),
+    ///     ]
+    /// };
+    /// let usb0 = Some("usb0");
+    ///
+    /// usage.update_from_counters(&counters(1000, 100), usb0, at(14, 12));
+    /// usage.update_from_counters(&counters(5000, 600), usb0, at(14, 23));
+    /// assert_eq!(usage.today, Totals { rx_bytes: 4000, tx_bytes: 500 });
+    ///
+    /// // Still the 14th until 4AM, however the interface was re-created
+    /// usage.update_from_counters(&counters(200, 100), usb0, at(15, 3));
+    /// assert_eq!(usage.today, Totals { rx_bytes: 4200, tx_bytes: 600 });
+    ///
+    /// // A new day and billing month
+    /// usage.update_from_counters(&counters(1200, 100), usb0, at(15, 5));
+    /// assert_eq!(usage.today, Totals { rx_bytes: 1000, tx_bytes: 0 });
+    /// assert_eq!(usage.month, Totals { rx_bytes: 1000, tx_bytes: 0 });
+    ///
+    /// // The clock was set back a day, which keeps today's totals
+    /// usage.update_from_counters(&counters(1500, 100), usb0, at(14, 12));
+    /// assert_eq!(usage.today, Totals { rx_bytes: 1300, tx_bytes: 0 });
+    /// assert_eq!(usage.month, Totals { rx_bytes: 1300, tx_bytes: 0 });
+    ///
+    /// // Unplugged, so that the data used on wlan0 doesn't count yet
+    /// usage.update_from_counters(&counters(1500, 100), None, at(15, 6));
+    /// assert_eq!(usage.today, Totals { rx_bytes: 1300, tx_bytes: 0 });
+    ///
+    /// // Totals are resumed from the state file
+    /// usage.save()?;
+    /// let usage = Usage::with_paths(None, &path, dev, route, time)?;
+    /// assert_eq!(usage.month, Totals { rx_bytes: 1300, tx_bytes: 0 });
+    /// # std::fs::remove_file(&path)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn update_from_counters(
+        &mut self,
+        counters: &[(String, u64, u64)],
+        default_interface: Option<&str>,
+        at: NaiveDateTime,
+    ) {
+        let day = self.get_day(at);
+        let month = self.get_month(at);
+
+        if day > self.day_start {
+            self.today = Totals::default();
+            self.day_start = day;
+        }
+        if month > self.month_start {
+            self.month = Totals::default();
+            self.month_start = month;
+        }
+
+        for (name, rx_bytes, tx_bytes) in counters {
+            let counted = match &self.interface_names {
+                Some(interface_names) => interface_names.contains(name),
+                None => Some(name.as_str()) == default_interface,
+            };
+            if !counted {
+                continue;
+            }
+
+            // Interfaces first seen only serve as the basis for the next sample
+            let (previous_rx, previous_tx) = self
+                .counters
+                .get(name)
+                .copied()
+                .unwrap_or((*rx_bytes, *tx_bytes));
+            let rx = delta(previous_rx, *rx_bytes);
+            let tx = delta(previous_tx, *tx_bytes);
+
+            self.today.rx_bytes += rx;
+            self.today.tx_bytes += tx;
+            self.month.rx_bytes += rx;
+            self.month.tx_bytes += tx;
+
+            self.counters.insert(name.clone(), (*rx_bytes, *tx_bytes));
+        }
+    }
+
+    /// Writes the totals and counters to the state file, creating its
+    /// directory if needed.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the state file can't be
+    /// written.
+    pub fn save(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut contents = format!(
+            "boot_id {}\nday_start {}\nmonth_start {}\ntoday {} {}\nmonth {} {}\n",
+            self.boot_id,
+            self.day_start,
+            self.month_start,
+            self.today.rx_bytes,
+            self.today.tx_bytes,
+            self.month.rx_bytes,
+            self.month.tx_bytes,
+        );
+        for (name, (rx_bytes, tx_bytes)) in &self.counters {
+            contents.push_str(&format!(
+                "counter {} {} {}\n",
+                name, rx_bytes, tx_bytes
+            ));
+        }
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // Written aside first so that a crash can't leave a truncated file
+        let temporary_path = self.path.with_extension("tmp");
+        fs::write(&temporary_path, contents)?;
+        fs::rename(&temporary_path, &self.path)?;
+
+        self.saved_at = Some(self.time.instant());
+
+        Ok(())
+    }
+
+    /// Restores the state saved in the given state file ```contents```,
+    /// skipping any malformed lines.
+    fn load(&mut self, contents: &str) {
+        let mut counters = HashMap::new();
+        let mut boot_id = "";
+
+        for line in contents.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let number = |index: usize| {
+                fields.get(index).and_then(|value| value.parse().ok())
+            };
+
+            match fields.first() {
+                Some(&"boot_id") => {
+                    boot_id = fields.get(1).copied().unwrap_or_default()
+                }
+                Some(&"day_start") => {
+                    if let Some(day) =
+                        fields.get(1).and_then(|day| day.parse().ok())
+                    {
+                        self.day_start = day;
+                    }
+                }
+                Some(&"month_start") => {
+                    if let Some(month) =
+                        fields.get(1).and_then(|month| month.parse().ok())
+                    {
+                        self.month_start = month;
+                    }
+                }
+                Some(&"today") => {
+                    if let (Some(rx_bytes), Some(tx_bytes)) =
+                        (number(1), number(2))
+                    {
+                        self.today = Totals { rx_bytes, tx_bytes };
+                    }
+                }
+                Some(&"month") => {
+                    if let (Some(rx_bytes), Some(tx_bytes)) =
+                        (number(1), number(2))
+                    {
+                        self.month = Totals { rx_bytes, tx_bytes };
+                    }
+                }
+                Some(&"counter") => {
+                    if let (Some(name), Some(rx_bytes), Some(tx_bytes)) =
+                        (fields.get(1), number(2), number(3))
+                    {
+                        counters
+                            .insert(String::from(*name), (rx_bytes, tx_bytes));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Counters restart from zero on every boot
+        self.counters = if boot_id == self.boot_id {
+            counters
+        } else {
+            counters.into_keys().map(|name| (name, (0, 0))).collect()
+        };
+    }
+
+    /// Returns the day the given time belongs to, which starts
+    /// at ```day_start_hour```.
+    fn get_day(&self, at: NaiveDateTime) -> NaiveDate {
+        (at - chrono::Duration::hours(i64::from(self.day_start_hour.min(23))))
+            .date()
+    }
+
+    /// Returns the first day of the month the given time belongs to, which
+    /// starts on ```month_start_day```.
+    fn get_month(&self, at: NaiveDateTime) -> NaiveDate {
+        let day = self.get_day(at);
+        let start_day = self.month_start_day.clamp(1, 28);
+
+        let (year, month) = match (day.day() >= start_day, day.month()) {
+            (true, month) => (day.year(), month),
+            (false, 1) => (day.year() - 1, 12),
+            (false, month) => (day.year(), month - 1),
+        };
+
+        // Days up to the 28th exist in every month
+        NaiveDate::from_ymd_opt(year, month, start_day).unwrap_or(day)
+    }
+}
+
+impl Status for Usage {
+    /// Saves the state file at most once every ```save_interval```, or right
+    /// away when a day or month started.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the sampled file can't be
+    /// read, if the routes can't be read when no interfaces were given, or if
+    /// the state file can't be written.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let contents = fs::read_to_string(&self.dev_path)?;
+        let default_interface = match self.interface_names {
+            Some(_) => None,
+            None => {
+                net::parse_default_route(&fs::read_to_string(&self.route_path)?)
+            }
+        };
+        let counters: Vec<(String, u64, u64)> =
+            network::parse_net_dev(&contents)
+                .into_iter()
+                .map(|stats| (stats.name, stats.rx_bytes, stats.tx_bytes))
+                .collect();
+        let period = (self.day_start, self.month_start);
+
+        self.update_from_counters(
+            &counters,
+            default_interface.as_deref(),
+            self.time.now().with_timezone(&Local).naive_local(),
+        );
+
+        let save_due = self.saved_at.is_none_or(|saved_at| {
+            self.time.instant().saturating_duration_since(saved_at)
+                >= self.save_interval
+        });
+
+        if save_due || period != (self.day_start, self.month_start) {
+            self.save()?;
+        }
+
+        Ok(())
+    }
+}
+
+fn delta(previous: u64, current: u64) -> u64 {
+    if current >= previous {
+        current - previous
+    } else {
+        current
+    }
+}
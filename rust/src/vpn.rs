@@ -0,0 +1,340 @@
+//! The ```vpn``` module provides a struct containing the state of the system's
+//! tunnel interfaces, such as tun, tap and WireGuard ones, along with the age
+//! of WireGuard peers' last handshake.
+
+use std::{
+    error::Error,
+    fs, io,
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    net,
+    netlink::{self, GenericMessage},
+    timesource::{SystemTimeSource, TimeSource},
+    Status,
+};
+
+/// The ```IFF_UP``` flag of ```/sys/class/net/*/flags```.
+static IFF_UP: u32 = 0x1;
+
+/// The ```ARPHRD_NONE``` and ```ARPHRD_ETHER``` types
+/// of ```/sys/class/net/*/type```, used by tun and tap interfaces respectively.
+static ARPHRD_NONE: u32 = 65534;
+static ARPHRD_ETHER: u32 = 1;
+
+static WG_CMD_GET_DEVICE: u8 = 0;
+static WG_GENL_VERSION: u8 = 1;
+static WGDEVICE_A_IFNAME: u16 = 2;
+static WGDEVICE_A_PEERS: u16 = 8;
+static WGPEER_A_LAST_HANDSHAKE_TIME: u16 = 6;
+
+pub struct Vpn {
+    pub tunnels: Vec<Tunnel>,
+    /// Whether to query WireGuard peers' last handshake, which requires
+    /// the ```CAP_NET_ADMIN``` capability and is turned off for good the
+    /// first time it's denied.
+    pub handshakes: bool,
+    interface_names: Option<Vec<String>>,
+    wireguard: Option<(netlink::Socket, u16)>,
+    time: Arc<dyn TimeSource>,
+}
+
+pub struct Tunnel {
+    pub name: String,
+    /// ```None``` for expected interfaces that don't exist.
+    pub kind: Option<TunnelKind>,
+    pub up: bool,
+    /// The most recent handshake among the interface's WireGuard peers, if
+    /// any happened and handshakes are queried.
+    pub last_handshake: Option<SystemTime>,
+    /// The time elapsed since ```last_handshake``` when the tunnel was
+    /// sampled.
+    pub handshake_age: Option<Duration>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TunnelKind {
+    Tun,
+    Tap,
+    WireGuard,
+}
+
+impl Vpn {
+    /// Where ```interface_names``` are the tunnels to display, which are shown
+    /// as down while they don't exist, or ```None``` to display every tunnel
+    /// found.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use dwmstatus::vpn::Vpn;
+    ///
+    /// let vpn_status = Vpn::new(Some(&["wg0", "tun0"]), false);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/sys/class/net``` can't
+    /// be read.
+    pub fn new(
+        interface_names: Option<&[&str]>,
+        handshakes: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        Vpn::with_time_source(
+            interface_names,
+            handshakes,
+            Arc::new(SystemTimeSource),
+        )
+    }
+
+    /// Same as ```Vpn::new```, except that the age of handshakes is measured
+    /// using the given ```time``` source rather than the system's clock.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/sys/class/net``` can't
+    /// be read.
+    pub fn with_time_source(
+        interface_names: Option<&[&str]>,
+        handshakes: bool,
+        time: Arc<dyn TimeSource>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut vpn = Vpn {
+            tunnels: Vec::new(),
+            handshakes,
+            interface_names: interface_names.map(|names| {
+                names.iter().map(|name| String::from(*name)).collect()
+            }),
+            wireguard: None,
+            time,
+        };
+
+        vpn.update()?;
+
+        Ok(vpn)
+    }
+
+    /// Returns the most recent handshake among the peers of the given
+    /// WireGuard interface, or ```None``` if it can't be queried.
+    fn get_last_handshake(
+        &mut self,
+        interface_name: &str,
+    ) -> Option<SystemTime> {
+        if self.wireguard.is_none() {
+            let mut socket = netlink::Socket::new().ok()?;
+            let family = socket.resolve_family("wireguard").ok()?;
+
+            self.wireguard = Some((socket, family));
+        }
+
+        let (socket, family) = self.wireguard.as_mut()?;
+        let mut name = interface_name.as_bytes().to_vec();
+        name.push(0);
+
+        let request = socket.request(
+            *family,
+            WG_CMD_GET_DEVICE,
+            WG_GENL_VERSION,
+            true,
+            &netlink::encode_attribute(WGDEVICE_A_IFNAME, &name),
+        );
+        let replies = match request {
+            Ok(replies) => replies,
+            Err(err) if is_permission_denied(err.as_ref()) => {
+                eprintln!(
+                    "dwmstatus: Not querying WireGuard handshakes \
+                     any further: {}",
+                    err
+                );
+                self.handshakes = false;
+                self.wireguard = None;
+                return None;
+            }
+            Err(_) => {
+                // The socket is opened again on the next query
+                self.wireguard = None;
+                return None;
+            }
+        };
+
+        // Devices with many peers are split across several messages
+        replies
+            .iter()
+            .filter_map(|reply| parse_last_handshake(reply).ok().flatten())
+            .max()
+    }
+}
+
+impl Status for Vpn {
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if ```/sys/class/net``` can't
+    /// be read.
+    fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let interfaces = net::get_interfaces()?;
+        let names = match &self.interface_names {
+            Some(interface_names) => interface_names.clone(),
+            None => interfaces
+                .iter()
+                .filter(|name| get_kind(name).is_some())
+                .cloned()
+                .collect(),
+        };
+
+        let now = SystemTime::from(self.time.now());
+        let mut tunnels = Vec::new();
+
+        for name in names {
+            let kind = if interfaces.contains(&name) {
+                get_kind(&name)
+            } else {
+                None
+            };
+            let up = kind.is_some() && is_up(&name);
+            let last_handshake = match kind {
+                Some(TunnelKind::WireGuard) if self.handshakes => {
+                    self.get_last_handshake(&name)
+                }
+                _ => None,
+            };
+
+            tunnels.push(Tunnel {
+                name,
+                kind,
+                up,
+                last_handshake,
+                handshake_age: last_handshake.and_then(|last_handshake| {
+                    now.duration_since(last_handshake).ok()
+                }),
+            });
+        }
+
+        self.tunnels = tunnels;
+
+        Ok(())
+    }
+}
+
+/// Returns the kind of tunnel described by the given sysfs ```uevent```
+/// contents and ```type```, where ```tun_flags``` tells whether the interface
+/// exposes a ```tun_flags``` attribute, which only tun and tap interfaces do.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::vpn::{detect_kind, TunnelKind};
+///
+/// let wireguard = "DEVTYPE=wireguard\nINTERFACE=wg0\nIFINDEX=5\n";
+/// let tun = "INTERFACE=tun0\nIFINDEX=6\n";
+///
+/// assert_eq!(
+///     detect_kind(wireguard, 65534, false),
+///     Some(TunnelKind::WireGuard),
+/// );
+/// assert_eq!(detect_kind(tun, 65534, true), Some(TunnelKind::Tun));
+/// assert_eq!(detect_kind("INTERFACE=tap0\n", 1, true), Some(TunnelKind::Tap));
+/// assert_eq!(detect_kind("INTERFACE=eth0\n", 1, false), None);
+/// ```
+pub fn detect_kind(
+    uevent: &str,
+    kind: u32,
+    tun_flags: bool,
+) -> Option<TunnelKind> {
+    if uevent.lines().any(|line| line == "DEVTYPE=wireguard") {
+        return Some(TunnelKind::WireGuard);
+    }
+
+    match (tun_flags, kind) {
+        (true, kind) if kind == ARPHRD_NONE => Some(TunnelKind::Tun),
+        (true, kind) if kind == ARPHRD_ETHER => Some(TunnelKind::Tap),
+        _ => None,
+    }
+}
+
+/// Returns the most recent handshake among the peers found in the given
+/// WireGuard device message payload, or ```None``` if none happened yet.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use dwmstatus::vpn::parse_last_handshake;
+///
+/// let payload = [
+///     0x00, 0x01, 0x00, 0x00,
+///     // The interface name
+///     0x08, 0x00, 0x02, 0x00, b'w', b'g', b'0', 0x00,
+///     // The nested peers, holding a single peer's last handshake
+///     0x1c, 0x00, 0x08, 0x80,
+///     0x18, 0x00, 0x00, 0x80,
+///     0x14, 0x00, 0x06, 0x00,
+///     0x00, 0xf1, 0x53, 0x65, 0x00, 0x00, 0x00, 0x00,
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+/// ];
+///
+/// assert_eq!(
+///     parse_last_handshake(&payload)?,
+///     Some(UNIX_EPOCH + Duration::from_secs(1700000000)),
+/// );
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if ```payload``` isn't a generic
+/// netlink message.
+pub fn parse_last_handshake(
+    payload: &[u8],
+) -> Result<Option<SystemTime>, Box<dyn Error>> {
+    let message = GenericMessage::parse(payload)?;
+    let peers = match message.attribute(WGDEVICE_A_PEERS) {
+        Some(peers) => peers.nested(),
+        None => return Ok(None),
+    };
+
+    Ok(peers
+        .iter()
+        .filter_map(|peer| {
+            let attributes = peer.nested();
+            let time = netlink::find_attribute(
+                &attributes,
+                WGPEER_A_LAST_HANDSHAKE_TIME,
+            )?;
+            let seconds = time.u64()?;
+
+            // Peers that never completed a handshake report zero
+            match seconds {
+                0 => None,
+                seconds => Some(UNIX_EPOCH + Duration::from_secs(seconds)),
+            }
+        })
+        .max())
+}
+
+fn get_kind(name: &str) -> Option<TunnelKind> {
+    let dir = Path::new("/sys/class/net").join(name);
+    let uevent = fs::read_to_string(dir.join("uevent")).unwrap_or_default();
+    let kind = fs::read_to_string(dir.join("type"))
+        .ok()
+        .and_then(|kind| kind.trim().parse().ok())
+        .unwrap_or(0);
+
+    detect_kind(&uevent, kind, dir.join("tun_flags").exists())
+}
+
+fn is_up(name: &str) -> bool {
+    fs::read_to_string(Path::new("/sys/class/net").join(name).join("flags"))
+        .ok()
+        .and_then(|flags| {
+            u32::from_str_radix(flags.trim().trim_start_matches("0x"), 16).ok()
+        })
+        .is_some_and(|flags| flags & IFF_UP != 0)
+}
+
+fn is_permission_denied(err: &(dyn Error + 'static)) -> bool {
+    err.downcast_ref::<io::Error>()
+        .is_some_and(|err| err.raw_os_error() == Some(libc::EPERM))
+}
@@ -1,62 +1,568 @@
 //! The ```wifi``` module provides a struct containing information related to
-//! the system's network interface card, such as whether the interface is
-//! currently active as well as the current connection's strength.
+//! the system's wireless interfaces, such as whether one is currently active,
+//! the network it's connected to as well as the connection's strength.
+//!
+//! When several wireless interfaces are connected, the one that currently has
+//! the default route is followed.
+//!
+//! Information is queried using nl80211 generic netlink messages, falling back
+//! to ```/proc/net/wireless``` when nl80211 is unavailable.
 
-use std::{error::Error, fs};
+use std::{error::Error, ffi::CString, fs, io};
 
-use crate::Status;
+use crate::{
+    net,
+    netlink::{self, GenericMessage},
+    Status,
+};
+
+static NL80211_CMD_GET_INTERFACE: u8 = 5;
+static NL80211_CMD_GET_STATION: u8 = 17;
+
+static NL80211_ATTR_IFINDEX: u16 = 3;
+static NL80211_ATTR_MAC: u16 = 6;
+static NL80211_ATTR_STA_INFO: u16 = 21;
+static NL80211_ATTR_WIPHY_FREQ: u16 = 38;
+static NL80211_ATTR_SSID: u16 = 52;
+
+static NL80211_STA_INFO_SIGNAL: u16 = 7;
+static NL80211_STA_INFO_TX_BITRATE: u16 = 8;
+static NL80211_STA_INFO_RX_BITRATE: u16 = 14;
+
+static NL80211_RATE_INFO_BITRATE: u16 = 1;
+static NL80211_RATE_INFO_BITRATE32: u16 = 5;
 
 pub struct Wifi {
     pub active: bool,
+    /// The connection's quality, as a percentage derived from ```signal_dbm```.
     pub strength: f32,
-    interface_name: String,
+    /// The name of the interface the status is about, if any is connected.
+    pub interface_name: Option<String>,
+    pub ssid: Option<String>,
+    /// The MAC address of the access point, such as ```aa:bb:cc:11:22:33```.
+    pub bssid: Option<String>,
+    pub signal_dbm: Option<i32>,
+    pub frequency_mhz: Option<u32>,
+    pub tx_bitrate_mbps: Option<f32>,
+    pub rx_bitrate_mbps: Option<f32>,
+    interface_names: Option<Vec<String>>,
+    nl80211: Option<(netlink::Socket, u16)>,
+    /// Set once nl80211 failed, after which only ```/proc/net/wireless``` is
+    /// used.
+    nl80211_failed: bool,
+}
+
+/// The parts of a nl80211 interface message used by ```Wifi```.
+pub struct Interface {
+    /// The network the interface is connected to, if any.
+    pub ssid: Option<String>,
+    pub frequency_mhz: Option<u32>,
+}
+
+/// The parts of a nl80211 station message used by ```Wifi```, where the
+/// station of a connected interface is its access point.
+pub struct Station {
+    pub bssid: String,
+    pub signal_dbm: Option<i32>,
+    pub tx_bitrate_mbps: Option<f32>,
+    pub rx_bitrate_mbps: Option<f32>,
 }
 
 impl Wifi {
-    /// Where ```interface_name``` is the name of the network interface card.
+    /// Discovers the system's wireless interfaces on every update, so that
+    /// interfaces which are plugged in or renamed later on are picked up.
     ///
     /// Because of the nature of how the status is retrieved, this method
-    /// will never return an ```Error```, even when given an
-    /// invalid ```interface_name```.
+    /// will never return an ```Error```, even when there's no wireless
+    /// interface at all.
     ///
     /// # Examples
     ///
     /// ```
-    /// let wifi_status = Wifi::new("wlp4s0");
+    /// use dwmstatus::wifi::Wifi;
+    ///
+    /// let wifi_status = Wifi::new();
     /// ```
-    pub fn new(interface_name: &str) -> Result<Self, Box<dyn Error>> {
-        Ok(Wifi {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        Ok(Wifi::with_interface_names(None))
+    }
+
+    /// Where ```interface_names``` are the names of the wireless interfaces to
+    /// consider, in order of preference when none has the default route.
+    ///
+    /// Same as ```Wifi::new```, this method will never return an ```Error```,
+    /// even when given invalid ```interface_names```.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dwmstatus::wifi::Wifi;
+    ///
+    /// let wifi_status = Wifi::with_interfaces(&["wlp4s0", "wlan0"]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_interfaces(
+        interface_names: &[&str],
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(Wifi::with_interface_names(Some(
+            interface_names
+                .iter()
+                .map(|name| String::from(*name))
+                .collect(),
+        )))
+    }
+
+    fn with_interface_names(interface_names: Option<Vec<String>>) -> Self {
+        Wifi {
             active: false,
             strength: 0.0,
-            interface_name: String::from(interface_name),
-        })
+            interface_name: None,
+            ssid: None,
+            bssid: None,
+            signal_dbm: None,
+            frequency_mhz: None,
+            tx_bitrate_mbps: None,
+            rx_bitrate_mbps: None,
+            interface_names,
+            nl80211: None,
+            nl80211_failed: false,
+        }
+    }
+
+    /// The channel of ```frequency_mhz```, please refer to ```get_channel```.
+    pub fn channel(&self) -> Option<u32> {
+        self.frequency_mhz.and_then(get_channel)
+    }
+
+    /// The band of ```frequency_mhz```, please refer to ```get_band```.
+    pub fn band(&self) -> Option<&'static str> {
+        self.frequency_mhz.and_then(get_band)
+    }
+
+    /// Updates the status using the given ```/proc/net/wireless``` contents and
+    /// the name of the interface that currently has the default route, which
+    /// only provides the interface's signal level.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dwmstatus::wifi::Wifi;
+    ///
+    /// let mut wifi = Wifi::with_interfaces(&["wlan0", "wlan1"])?;
+    /// let contents = "\
+    ///     Inter-| sta-|   Quality        |   Discarded packets\n\
+    ///      face | tus | link level noise |  nwid  crypt   frag\n\
+    ///     wlan10: 0000   70.  -40.  -256        0      0      0      0\n\
+    ///      wlan1: 0000   35.  -75.  -256        0      0      0      0\n\
+    ///      wlan0: 0000   56.  -54.  -256        0      0      0      0\n";
+    ///
+    /// wifi.update_from_wireless(contents, Some("wlan1"))?;
+    /// assert_eq!(wifi.interface_name.as_deref(), Some("wlan1"));
+    /// assert_eq!(wifi.strength, 50.0);
+    ///
+    /// wifi.update_from_wireless(contents, None)?;
+    /// assert_eq!(wifi.interface_name.as_deref(), Some("wlan0"));
+    /// assert_eq!(wifi.signal_dbm, Some(-54));
+    /// assert_eq!(wifi.strength, 92.0);
+    ///
+    /// wifi.update_from_wireless(contents, Some("eth0"))?;
+    /// assert_eq!(wifi.interface_name.as_deref(), Some("wlan0"));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the wireless interfaces
+    /// weren't given explicitly and can't be discovered.
+    pub fn update_from_wireless(
+        &mut self,
+        contents: &str,
+        default_interface: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let levels = parse_wireless(contents);
+        let connected: Vec<&(String, i32)> = self
+            .get_interface_names()?
+            .iter()
+            .filter_map(|name| {
+                levels.iter().find(|(interface, _)| interface == name)
+            })
+            .collect();
+
+        self.clear();
+
+        if let Some((interface, signal_dbm)) =
+            follow(&connected, default_interface)
+        {
+            self.interface_name = Some(interface.clone());
+            self.signal_dbm = Some(*signal_dbm);
+            self.strength = get_quality(*signal_dbm);
+            self.active = true;
+        }
+
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// This method will return an ```Error``` if the nl80211 family can't be
+    /// resolved, or if the wireless interfaces weren't given explicitly and
+    /// can't be discovered.
+    fn update_from_nl80211(
+        &mut self,
+        default_interface: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.nl80211.is_none() {
+            let mut socket = netlink::Socket::new()?;
+            let family = socket.resolve_family("nl80211")?;
+
+            self.nl80211 = Some((socket, family));
+        }
+
+        let interface_names = self.get_interface_names()?;
+        let (socket, family) =
+            self.nl80211.as_mut().ok_or("No nl80211 socket found!")?;
+
+        let mut connected: Vec<(String, u32, Interface)> = Vec::new();
+
+        for name in interface_names {
+            let index = match get_interface_index(&name) {
+                Some(index) => index,
+                None => continue,
+            };
+            let request = socket.request(
+                *family,
+                NL80211_CMD_GET_INTERFACE,
+                0,
+                false,
+                &netlink::encode_attribute(
+                    NL80211_ATTR_IFINDEX,
+                    &index.to_ne_bytes(),
+                ),
+            );
+
+            // Interfaces may vanish at any point, in which case they're
+            // skipped, while any other failure gives up on the socket
+            let replies = match request {
+                Ok(replies) => replies,
+                Err(err) if is_no_device(err.as_ref()) => continue,
+                Err(err) => return Err(err),
+            };
+            let interface =
+                match replies.first().map(|reply| parse_interface(reply)) {
+                    Some(interface) => interface?,
+                    None => continue,
+                };
+
+            if interface.ssid.is_some() {
+                connected.push((name, index, interface));
+            }
+        }
+
+        let followed = connected
+            .iter()
+            .find(|(name, _, _)| Some(name.as_str()) == default_interface)
+            .or_else(|| connected.first());
+
+        let station = match followed {
+            Some((_, index, _)) => socket
+                .request(
+                    *family,
+                    NL80211_CMD_GET_STATION,
+                    0,
+                    true,
+                    &netlink::encode_attribute(
+                        NL80211_ATTR_IFINDEX,
+                        &index.to_ne_bytes(),
+                    ),
+                )?
+                .first()
+                .map(|reply| parse_station(reply))
+                .transpose()?,
+            None => None,
+        };
+
+        self.clear();
+
+        if let Some((name, _, interface)) = followed {
+            self.interface_name = Some(name.clone());
+            self.ssid = interface.ssid.clone();
+            self.frequency_mhz = interface.frequency_mhz;
+            self.active = true;
+        }
+
+        if let Some(station) = station {
+            self.bssid = Some(station.bssid);
+            self.signal_dbm = station.signal_dbm;
+            self.strength = station.signal_dbm.map_or(0.0, get_quality);
+            self.tx_bitrate_mbps = station.tx_bitrate_mbps;
+            self.rx_bitrate_mbps = station.rx_bitrate_mbps;
+        }
+
+        Ok(())
+    }
+
+    fn get_interface_names(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        match &self.interface_names {
+            Some(interface_names) => Ok(interface_names.clone()),
+            None => net::get_wireless_interfaces(),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.active = false;
+        self.strength = 0.0;
+        self.interface_name = None;
+        self.ssid = None;
+        self.bssid = None;
+        self.signal_dbm = None;
+        self.frequency_mhz = None;
+        self.tx_bitrate_mbps = None;
+        self.rx_bitrate_mbps = None;
     }
 }
 
 impl Status for Wifi {
+    /// Falls back to ```/proc/net/wireless``` for good the first time nl80211
+    /// fails, such as when the ```cfg80211``` module isn't loaded, after
+    /// logging the reason.
+    ///
     /// # Errors
     ///
-    /// This method will return an ```Error``` if ```/proc/net/wireless```
-    /// can't be opened for reading.
+    /// This method will return an ```Error``` if ```/proc/net/route``` can't
+    /// be opened for reading, or if nl80211 is unavailable
+    /// and ```/proc/net/wireless``` can't be opened for reading either.
     fn update(&mut self) -> Result<(), Box<dyn Error>> {
+        let default_interface = net::get_default_route_interface()?;
+
+        if !self.nl80211_failed {
+            match self.update_from_nl80211(default_interface.as_deref()) {
+                Ok(()) => return Ok(()),
+                Err(err) => {
+                    eprintln!(
+                        "dwmstatus: nl80211 failed, falling back \
+                         to /proc/net/wireless: {}",
+                        err
+                    );
+                    self.nl80211 = None;
+                    self.nl80211_failed = true;
+                }
+            }
+        }
+
         let contents = fs::read_to_string("/proc/net/wireless")?;
 
-        for line in contents.lines().skip(2) {
-            let mut values = line.split_whitespace();
+        self.update_from_wireless(&contents, default_interface.as_deref())
+    }
+}
+
+/// Parses the given nl80211 interface message payload.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::wifi::parse_interface;
+///
+/// let payload = [
+///     0x07, 0x01, 0x00, 0x00,
+///     // The interface index
+///     0x08, 0x00, 0x03, 0x00, 0x03, 0x00, 0x00, 0x00,
+///     // The SSID
+///     0x08, 0x00, 0x34, 0x00, b'h', b'o', b'm', b'e',
+///     // The frequency
+///     0x08, 0x00, 0x26, 0x00, 0x3c, 0x14, 0x00, 0x00,
+/// ];
+///
+/// let interface = parse_interface(&payload)?;
+/// assert_eq!(interface.ssid.as_deref(), Some("home"));
+/// assert_eq!(interface.frequency_mhz, Some(5180));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if ```payload``` isn't a generic
+/// netlink message.
+pub fn parse_interface(payload: &[u8]) -> Result<Interface, Box<dyn Error>> {
+    let message = GenericMessage::parse(payload)?;
 
-            if values.next().unwrap().contains(&self.interface_name) {
-                let link_quality: f32 = values.skip(1).next().unwrap().parse().unwrap();
+    Ok(Interface {
+        // SSIDs are arbitrary bytes rather than nul terminated strings
+        ssid: message
+            .attribute(NL80211_ATTR_SSID)
+            .map(|ssid| String::from_utf8_lossy(ssid.payload).into_owned()),
+        frequency_mhz: message
+            .attribute(NL80211_ATTR_WIPHY_FREQ)
+            .and_then(|frequency| frequency.u32()),
+    })
+}
 
-                self.strength = link_quality * 100.0 / 70.0;
-                self.active = true;
+/// Parses the given nl80211 station message payload.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::wifi::parse_station;
+///
+/// let payload = [
+///     0x13, 0x01, 0x00, 0x00,
+///     // The MAC address
+///     0x0a, 0x00, 0x06, 0x00, 0xaa, 0xbb, 0xcc, 0x11, 0x22, 0x33, 0x00, 0x00,
+///     // The nested station information
+///     0x24, 0x00, 0x15, 0x80,
+///     0x05, 0x00, 0x07, 0x00, 0xc4, 0x00, 0x00, 0x00,
+///     0x0c, 0x00, 0x08, 0x80, 0x08, 0x00, 0x05, 0x00, 0xa4, 0x06, 0x00, 0x00,
+///     0x0a, 0x00, 0x0e, 0x80, 0x06, 0x00, 0x01, 0x00, 0x90, 0x01, 0x00, 0x00,
+/// ];
+///
+/// let station = parse_station(&payload)?;
+/// assert_eq!(station.bssid, "aa:bb:cc:11:22:33");
+/// assert_eq!(station.signal_dbm, Some(-60));
+/// assert_eq!(station.tx_bitrate_mbps, Some(170.0));
+/// assert_eq!(station.rx_bitrate_mbps, Some(40.0));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Errors
+///
+/// This function will return an ```Error``` if ```payload``` isn't a generic
+/// netlink message, or if it has no MAC address.
+pub fn parse_station(payload: &[u8]) -> Result<Station, Box<dyn Error>> {
+    let message = GenericMessage::parse(payload)?;
+    let bssid = message
+        .attribute(NL80211_ATTR_MAC)
+        .map(|mac| {
+            mac.payload
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<Vec<String>>()
+                .join(":")
+        })
+        .ok_or("No station MAC address found!")?;
 
-                return Ok(());
-            }
-        }
+    let info = message
+        .attribute(NL80211_ATTR_STA_INFO)
+        .map(|info| info.nested())
+        .unwrap_or_default();
+    let bitrate = |kind| {
+        let rate_info = netlink::find_attribute(&info, kind)?.nested();
 
-        self.strength = 0.0;
-        self.active = false;
+        // Bitrates are expressed in units of 100kbit/s
+        netlink::find_attribute(&rate_info, NL80211_RATE_INFO_BITRATE32)
+            .and_then(|bitrate| bitrate.u32())
+            .or_else(|| {
+                netlink::find_attribute(&rate_info, NL80211_RATE_INFO_BITRATE)
+                    .and_then(|bitrate| bitrate.u16())
+                    .map(u32::from)
+            })
+            .map(|bitrate| bitrate as f32 / 10.0)
+    };
 
-        Ok(())
+    Ok(Station {
+        bssid,
+        signal_dbm: netlink::find_attribute(&info, NL80211_STA_INFO_SIGNAL)
+            .and_then(|signal| signal.u8())
+            .map(|signal| signal as i8 as i32),
+        tx_bitrate_mbps: bitrate(NL80211_STA_INFO_TX_BITRATE),
+        rx_bitrate_mbps: bitrate(NL80211_STA_INFO_RX_BITRATE),
+    })
+}
+
+/// Returns the name and signal level in dBm of every interface found in the
+/// given ```/proc/net/wireless``` contents, skipping any malformed lines.
+pub fn parse_wireless(contents: &str) -> Vec<(String, i32)> {
+    contents
+        .lines()
+        .skip(2)
+        .filter_map(|line| {
+            let (interface, values) = line.split_once(':')?;
+            let level = values.split_whitespace().nth(2)?;
+
+            Some((
+                String::from(interface.trim()),
+                level.trim_end_matches('.').parse().ok()?,
+            ))
+        })
+        .collect()
+}
+
+/// Maps a signal level in dBm to a quality percentage, where -100dBm and
+/// below is unusable and -50dBm and above is excellent.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::wifi::get_quality;
+///
+/// assert_eq!(get_quality(-40), 100.0);
+/// assert_eq!(get_quality(-67), 66.0);
+/// assert_eq!(get_quality(-110), 0.0);
+/// ```
+pub fn get_quality(signal_dbm: i32) -> f32 {
+    (2 * (signal_dbm + 100)).clamp(0, 100) as f32
+}
+
+/// Returns the channel number of the given frequency.
+///
+/// # Examples
+///
+/// ```
+/// use dwmstatus::wifi::get_channel;
+///
+/// assert_eq!(get_channel(2437), Some(6));
+/// assert_eq!(get_channel(2484), Some(14));
+/// assert_eq!(get_channel(5180), Some(36));
+/// assert_eq!(get_channel(5885), Some(177));
+/// assert_eq!(get_channel(5925), None);
+/// assert_eq!(get_channel(5935), Some(2));
+/// assert_eq!(get_channel(5955), Some(1));
+/// assert_eq!(get_channel(5975), Some(5));
+/// assert_eq!(get_channel(7115), Some(233));
+/// ```
+pub fn get_channel(frequency_mhz: u32) -> Option<u32> {
+    match frequency_mhz {
+        2484 => Some(14),
+        2412..=2472 => Some((frequency_mhz - 2407) / 5),
+        5000..=5900 => Some((frequency_mhz - 5000) / 5),
+        // The 6GHz band's channel 2 predates its regular channel spacing
+        5935 => Some(2),
+        5955..=7115 => Some((frequency_mhz - 5950) / 5),
+        _ => None,
     }
 }
+
+/// Returns the band of the given frequency, such as ```5GHz```.
+pub fn get_band(frequency_mhz: u32) -> Option<&'static str> {
+    match frequency_mhz {
+        2400..=2500 => Some("2.4GHz"),
+        4900..=5924 => Some("5GHz"),
+        5925..=7125 => Some("6GHz"),
+        _ => None,
+    }
+}
+
+/// Returns the interface that has the default route if it's part of the
+/// given ```interfaces```, or the first one otherwise.
+fn follow<'a, T>(
+    interfaces: &[&'a (String, T)],
+    default_interface: Option<&str>,
+) -> Option<&'a (String, T)> {
+    interfaces
+        .iter()
+        .find(|(name, _)| Some(name.as_str()) == default_interface)
+        .or_else(|| interfaces.first())
+        .copied()
+}
+
+fn get_interface_index(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+
+    match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+        0 => None,
+        index => Some(index),
+    }
+}
+
+fn is_no_device(err: &(dyn Error + 'static)) -> bool {
+    err.downcast_ref::<io::Error>()
+        .is_some_and(|err| err.raw_os_error() == Some(libc::ENODEV))
+}